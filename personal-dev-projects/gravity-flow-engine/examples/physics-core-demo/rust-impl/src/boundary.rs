@@ -0,0 +1,167 @@
+// Walls around the width x height canvas, applied after every integration step
+
+use crate::physics_engine::Node;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Boundary {
+    // Nodes stop at the walls, a radius away from them, losing the velocity
+    // component that points out of the canvas
+    Clamp,
+    // Nodes reflect off the walls, keeping `restitution` of their speed
+    // toward the wall
+    Bounce { restitution: f64 },
+    // Nodes within `margin` of a wall are pushed back, proportionally to
+    // how far inside the margin they are. Fast nodes can still cross.
+    Soft { margin: f64, strength: f64 },
+    // A node leaving through one side re-enters through the opposite one.
+    // Forces still measure plain distances, not distances across the seam.
+    Wrap,
+}
+
+impl Boundary {
+    // Elastic walls
+    pub fn bounce() -> Self {
+        Boundary::Bounce { restitution: 1.0 }
+    }
+
+    pub fn apply(&self, nodes: &mut [Node], width: f64, height: f64, dt: f64) {
+        for node in nodes.iter_mut().filter(|node| !node.physical_properties.fixed) {
+            let radius = node.physical_properties.radius.max(0.0);
+            if node.fx.is_none() {
+                (node.x, node.vx) = self.apply_axis(node.x, node.vx, radius, width, dt);
+            }
+            if node.fy.is_none() {
+                (node.y, node.vy) = self.apply_axis(node.y, node.vy, radius, height, dt);
+            }
+        }
+    }
+
+    // New position and velocity along one axis spanning 0..extent
+    fn apply_axis(&self, position: f64, velocity: f64, radius: f64, extent: f64, dt: f64) -> (f64, f64) {
+        // Nodes wider than the canvas sit in the middle
+        let (low, high) = if 2.0 * radius < extent {
+            (radius, extent - radius)
+        } else {
+            (extent / 2.0, extent / 2.0)
+        };
+
+        match *self {
+            Boundary::Clamp => {
+                if position < low {
+                    (low, velocity.max(0.0))
+                } else if position > high {
+                    (high, velocity.min(0.0))
+                } else {
+                    (position, velocity)
+                }
+            }
+            Boundary::Bounce { restitution } => {
+                if position < low {
+                    let reflected = low + (low - position) * restitution;
+                    (reflected.min(high), velocity.abs() * restitution)
+                } else if position > high {
+                    let reflected = high - (position - high) * restitution;
+                    (reflected.max(low), -velocity.abs() * restitution)
+                } else {
+                    (position, velocity)
+                }
+            }
+            Boundary::Soft { margin, strength } => {
+                let push = if position < low + margin {
+                    (low + margin - position) * strength
+                } else if position > high - margin {
+                    (high - margin - position) * strength
+                } else {
+                    0.0
+                };
+                (position, velocity + push * dt)
+            }
+            Boundary::Wrap if extent > 0.0 => (position.rem_euclid(extent), velocity),
+            Boundary::Wrap => (position, velocity),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::PhysicalProperties;
+    use std::collections::BTreeMap;
+
+    fn node(x: f64, y: f64, vx: f64, vy: f64) -> Node {
+        Node {
+            id: "a".to_string(),
+            label: String::new(),
+            category: String::new(),
+            physical_properties: PhysicalProperties {
+                mass: 1.0,
+                charge: 0.0,
+                friction: 0.0,
+                radius: 10.0,
+                fixed: false,
+                initial_position: None,
+            },
+            metadata: BTreeMap::new(),
+            x,
+            y,
+            vx,
+            vy,
+            fx: None,
+            fy: None,
+        }
+    }
+
+    // State of one node after the boundary of a 100 x 100 canvas
+    fn apply(boundary: Boundary, node: Node) -> (f64, f64, f64, f64) {
+        let mut nodes = [node];
+        boundary.apply(&mut nodes, 100.0, 100.0, 1.0);
+        (nodes[0].x, nodes[0].y, nodes[0].vx, nodes[0].vy)
+    }
+
+    #[test]
+    fn clamp_stops_nodes_a_radius_inside_the_walls() {
+        assert_eq!(apply(Boundary::Clamp, node(5.0, 120.0, -3.0, 4.0)), (10.0, 90.0, 0.0, 0.0));
+        // Velocity back into the canvas is kept
+        assert_eq!(apply(Boundary::Clamp, node(5.0, 50.0, 3.0, -4.0)), (10.0, 50.0, 3.0, -4.0));
+        assert_eq!(apply(Boundary::Clamp, node(50.0, 50.0, -3.0, 4.0)), (50.0, 50.0, -3.0, 4.0));
+    }
+
+    #[test]
+    fn bounce_reflects_position_and_velocity() {
+        assert_eq!(apply(Boundary::bounce(), node(4.0, 95.0, -3.0, 4.0)), (16.0, 85.0, 3.0, -4.0));
+        assert_eq!(
+            apply(Boundary::Bounce { restitution: 0.5 }, node(4.0, 95.0, -3.0, 4.0)),
+            (13.0, 87.5, 1.5, -2.0)
+        );
+        // A reflection can't carry a node past the opposite wall
+        assert_eq!(apply(Boundary::bounce(), node(-200.0, 50.0, -3.0, 0.0)), (90.0, 50.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn soft_walls_push_back_inside_the_margin() {
+        let soft = Boundary::Soft { margin: 20.0, strength: 0.5 };
+        // 10 into the margin on the left, 5 on the bottom
+        assert_eq!(apply(soft.clone(), node(20.0, 75.0, 0.0, 1.0)), (20.0, 75.0, 5.0, -1.5));
+        assert_eq!(apply(soft, node(50.0, 50.0, 1.0, 1.0)), (50.0, 50.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn wrap_reenters_through_the_opposite_side() {
+        assert_eq!(apply(Boundary::Wrap, node(-10.0, 130.0, -3.0, 4.0)), (90.0, 30.0, -3.0, 4.0));
+    }
+
+    #[test]
+    fn fixed_nodes_pinned_axes_and_oversized_nodes() {
+        let mut fixed = node(-50.0, -50.0, 0.0, 0.0);
+        fixed.physical_properties.fixed = true;
+        assert_eq!(apply(Boundary::Clamp, fixed), (-50.0, -50.0, 0.0, 0.0));
+
+        let mut pinned = node(-50.0, -50.0, 0.0, 0.0);
+        pinned.fx = Some(-50.0);
+        assert_eq!(apply(Boundary::Clamp, pinned), (-50.0, 10.0, 0.0, 0.0));
+
+        let mut wide = node(0.0, 0.0, 0.0, 0.0);
+        wide.physical_properties.radius = 80.0;
+        assert_eq!(apply(Boundary::Clamp, wide), (50.0, 50.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,356 @@
+// Declarative layout constraints, enforced by projecting positions after
+// every integration step (as cola.js does)
+
+use crate::error::PhysicsError;
+use crate::graph::NodeIndex;
+use crate::physics_engine::Node;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constraint {
+    // All nodes share the same y
+    AlignHorizontal(Vec<String>),
+    // All nodes share the same x
+    AlignVertical(Vec<String>),
+    // `right` is at least `gap` past `left` along the axis
+    Separation { axis: Axis, left: String, right: String, gap: f64 },
+    // The two nodes are exactly `distance` apart
+    Distance { a: String, b: String, distance: f64 },
+    // Node centers stay inside the rectangle
+    WithinRectangle { nodes: Vec<String>, x: f64, y: f64, width: f64, height: f64 },
+}
+
+// Constraint with its node ids resolved to indices
+#[derive(Clone, Debug)]
+enum Resolved {
+    Align { axis: Axis, nodes: Vec<usize> },
+    Separation { axis: Axis, left: usize, right: usize, gap: f64 },
+    Distance { a: usize, b: usize, distance: f64 },
+    WithinRectangle { nodes: Vec<usize>, x: f64, y: f64, width: f64, height: f64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct ConstraintSolver {
+    // Gauss-Seidel passes over all constraints per tick
+    pub iterations: usize,
+    constraints: Vec<Constraint>,
+    resolved: Vec<Resolved>,
+}
+
+impl Default for ConstraintSolver {
+    fn default() -> Self {
+        ConstraintSolver {
+            iterations: 10,
+            constraints: Vec::new(),
+            resolved: Vec::new(),
+        }
+    }
+}
+
+impl ConstraintSolver {
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
+    pub(crate) fn add(&mut self, constraint: Constraint, index: &NodeIndex) -> Result<(), PhysicsError> {
+        self.resolved.push(resolve(&constraint, index)?);
+        self.constraints.push(constraint);
+        Ok(())
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.constraints.clear();
+        self.resolved.clear();
+    }
+
+    // Resolves every constraint against a new graph; nothing changes on error
+    pub(crate) fn rebind(&mut self, index: &NodeIndex) -> Result<(), PhysicsError> {
+        self.resolved = self
+            .constraints
+            .iter()
+            .map(|constraint| resolve(constraint, index))
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    // Moves nodes the least amount needed to satisfy the constraints. Heavy
+    // nodes move less; fixed nodes and pinned axes don't move at all.
+    pub fn project(&self, nodes: &mut [Node]) {
+        if self.resolved.is_empty() {
+            return;
+        }
+
+        for _ in 0..self.iterations {
+            for constraint in &self.resolved {
+                match constraint {
+                    Resolved::Align { axis, nodes: members } => align(nodes, members, *axis),
+                    Resolved::Separation { axis, left, right, gap } => {
+                        let separation = coordinate(&nodes[*right], *axis) - coordinate(&nodes[*left], *axis);
+                        if separation < *gap {
+                            shift_pair(nodes, *left, *right, *axis, *gap - separation);
+                        }
+                    }
+                    Resolved::Distance { a, b, distance } => keep_distance(nodes, *a, *b, *distance),
+                    Resolved::WithinRectangle { nodes: members, x, y, width, height } => {
+                        for &i in members {
+                            let node = &mut nodes[i];
+                            if weight(node, Axis::X) > 0.0 {
+                                node.x = node.x.clamp(*x, x + width.max(0.0));
+                            }
+                            if weight(node, Axis::Y) > 0.0 {
+                                node.y = node.y.clamp(*y, y + height.max(0.0));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn resolve(constraint: &Constraint, index: &NodeIndex) -> Result<Resolved, PhysicsError> {
+    let find = |id: &String| index.get(id).copied().ok_or_else(|| PhysicsError::UnknownNode(id.clone()));
+    let find_all = |ids: &[String]| ids.iter().map(find).collect::<Result<Vec<_>, _>>();
+
+    Ok(match constraint {
+        Constraint::AlignHorizontal(ids) => Resolved::Align { axis: Axis::Y, nodes: find_all(ids)? },
+        Constraint::AlignVertical(ids) => Resolved::Align { axis: Axis::X, nodes: find_all(ids)? },
+        Constraint::Separation { axis, left, right, gap } => Resolved::Separation {
+            axis: *axis,
+            left: find(left)?,
+            right: find(right)?,
+            gap: *gap,
+        },
+        Constraint::Distance { a, b, distance } => Resolved::Distance {
+            a: find(a)?,
+            b: find(b)?,
+            distance: *distance,
+        },
+        Constraint::WithinRectangle { nodes, x, y, width, height } => Resolved::WithinRectangle {
+            nodes: find_all(nodes)?,
+            x: *x,
+            y: *y,
+            width: *width,
+            height: *height,
+        },
+    })
+}
+
+fn coordinate(node: &Node, axis: Axis) -> f64 {
+    match axis {
+        Axis::X => node.x,
+        Axis::Y => node.y,
+    }
+}
+
+fn coordinate_mut(node: &mut Node, axis: Axis) -> &mut f64 {
+    match axis {
+        Axis::X => &mut node.x,
+        Axis::Y => &mut node.y,
+    }
+}
+
+// Inverse mass along an axis; zero for nodes that must not move
+fn weight(node: &Node, axis: Axis) -> f64 {
+    let pinned = match axis {
+        Axis::X => node.fx.is_some(),
+        Axis::Y => node.fy.is_some(),
+    };
+    if node.physical_properties.fixed || pinned {
+        0.0
+    } else {
+        node.physical_properties.inverse_mass()
+    }
+}
+
+fn align(nodes: &mut [Node], members: &[usize], axis: Axis) {
+    // Immovable members decide the line; otherwise it's the center of mass
+    let anchored: Vec<f64> = members
+        .iter()
+        .filter(|&&i| weight(&nodes[i], axis) == 0.0)
+        .map(|&i| coordinate(&nodes[i], axis))
+        .collect();
+    let target = if anchored.is_empty() {
+        let (sum, mass) = members.iter().fold((0.0, 0.0), |(sum, mass), &i| {
+            let m = 1.0 / weight(&nodes[i], axis);
+            (sum + coordinate(&nodes[i], axis) * m, mass + m)
+        });
+        if mass == 0.0 {
+            return;
+        }
+        sum / mass
+    } else {
+        anchored.iter().sum::<f64>() / anchored.len() as f64
+    };
+
+    for &i in members {
+        if weight(&nodes[i], axis) > 0.0 {
+            *coordinate_mut(&mut nodes[i], axis) = target;
+        }
+    }
+}
+
+// Moves `right` forward and `left` back along the axis by `amount` in total
+fn shift_pair(nodes: &mut [Node], left: usize, right: usize, axis: Axis, amount: f64) {
+    let (wl, wr) = (weight(&nodes[left], axis), weight(&nodes[right], axis));
+    if wl + wr == 0.0 {
+        return;
+    }
+    *coordinate_mut(&mut nodes[left], axis) -= amount * wl / (wl + wr);
+    *coordinate_mut(&mut nodes[right], axis) += amount * wr / (wl + wr);
+}
+
+fn keep_distance(nodes: &mut [Node], a: usize, b: usize, distance: f64) {
+    let (dx, dy) = (nodes[b].x - nodes[a].x, nodes[b].y - nodes[a].y);
+    let current = (dx * dx + dy * dy).sqrt();
+    // Coincident nodes have no direction to separate along
+    if current == 0.0 {
+        return;
+    }
+
+    let error = (current - distance) / current;
+    for (axis, delta) in [(Axis::X, dx), (Axis::Y, dy)] {
+        let (wa, wb) = (weight(&nodes[a], axis), weight(&nodes[b], axis));
+        if wa + wb == 0.0 {
+            continue;
+        }
+        *coordinate_mut(&mut nodes[a], axis) += delta * error * wa / (wa + wb);
+        *coordinate_mut(&mut nodes[b], axis) -= delta * error * wb / (wa + wb);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::node_index;
+    use crate::physics_engine::{PhysicalProperties, PhysicsEngine};
+    use std::collections::BTreeMap;
+
+    fn node(id: &str, x: f64, y: f64, mass: f64) -> Node {
+        Node {
+            id: id.to_string(),
+            label: String::new(),
+            category: String::new(),
+            physical_properties: PhysicalProperties {
+                mass,
+                charge: 0.0,
+                friction: 0.0,
+                radius: 1.0,
+                fixed: false,
+                initial_position: None,
+            },
+            metadata: BTreeMap::new(),
+            x,
+            y,
+            vx: 0.0,
+            vy: 0.0,
+            fx: None,
+            fy: None,
+        }
+    }
+
+    // Projects the nodes onto a single constraint
+    fn project(nodes: &mut [Node], constraint: Constraint) {
+        let mut solver = ConstraintSolver::default();
+        solver.add(constraint, &node_index(nodes).unwrap()).unwrap();
+        solver.project(nodes);
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn align_meets_at_the_center_of_mass_or_the_fixed_member() {
+        let mut nodes = [node("a", 0.0, 0.0, 1.0), node("b", 50.0, 40.0, 3.0)];
+        project(&mut nodes, Constraint::AlignHorizontal(ids(&["a", "b"])));
+        assert_eq!((nodes[0].y, nodes[1].y), (30.0, 30.0));
+        assert_eq!((nodes[0].x, nodes[1].x), (0.0, 50.0));
+
+        let mut nodes = [node("a", 0.0, 0.0, 1.0), node("b", 50.0, 40.0, 3.0), node("c", 10.0, 0.0, 1.0)];
+        nodes[2].physical_properties.fixed = true;
+        nodes[2].x = 5.0;
+        project(&mut nodes, Constraint::AlignVertical(ids(&["a", "b", "c"])));
+        assert!(nodes.iter().all(|node| node.x == 5.0));
+    }
+
+    #[test]
+    fn separation_pushes_both_nodes_unless_one_is_pinned() {
+        let separation = || Constraint::Separation { axis: Axis::X, left: "a".to_string(), right: "b".to_string(), gap: 30.0 };
+
+        let mut nodes = [node("a", 0.0, 0.0, 1.0), node("b", 10.0, 0.0, 1.0)];
+        project(&mut nodes, separation());
+        assert_eq!((nodes[0].x, nodes[1].x), (-10.0, 20.0));
+
+        let mut nodes = [node("a", 0.0, 0.0, 1.0), node("b", 10.0, 0.0, 1.0)];
+        nodes[1].fx = Some(10.0);
+        project(&mut nodes, separation());
+        assert_eq!((nodes[0].x, nodes[1].x), (-20.0, 10.0));
+
+        // Already far enough apart
+        let mut nodes = [node("a", 0.0, 0.0, 1.0), node("b", 40.0, 0.0, 1.0)];
+        project(&mut nodes, separation());
+        assert_eq!((nodes[0].x, nodes[1].x), (0.0, 40.0));
+    }
+
+    #[test]
+    fn distance_moves_the_lighter_node_further() {
+        let distance = || Constraint::Distance { a: "a".to_string(), b: "b".to_string(), distance: 40.0 };
+
+        let mut nodes = [node("a", 0.0, 0.0, 3.0), node("b", 10.0, 0.0, 1.0)];
+        project(&mut nodes, distance());
+        assert_eq!((nodes[0].x, nodes[1].x), (-7.5, 32.5));
+
+        let mut nodes = [node("a", 0.0, 0.0, 1.0), node("b", 6.0, 8.0, 1.0)];
+        nodes[0].physical_properties.fixed = true;
+        project(&mut nodes, distance());
+        assert_eq!((nodes[0].x, nodes[0].y), (0.0, 0.0));
+        assert!((nodes[1].x - 24.0).abs() < 1e-9 && (nodes[1].y - 32.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rectangle_clamps_free_axes_only() {
+        let mut nodes = [node("a", -20.0, 150.0, 1.0), node("b", 200.0, 50.0, 1.0), node("c", 50.0, 50.0, 1.0)];
+        nodes[1].fx = Some(200.0);
+        project(
+            &mut nodes,
+            Constraint::WithinRectangle { nodes: ids(&["a", "b", "c"]), x: 0.0, y: 0.0, width: 100.0, height: 100.0 },
+        );
+        assert_eq!((nodes[0].x, nodes[0].y), (0.0, 100.0));
+        assert_eq!((nodes[1].x, nodes[1].y), (200.0, 50.0));
+        assert_eq!((nodes[2].x, nodes[2].y), (50.0, 50.0));
+    }
+
+    #[test]
+    fn constraints_must_name_existing_nodes() {
+        let nodes = vec![node("a", 0.0, 0.0, 1.0), node("b", 10.0, 0.0, 1.0)];
+        let mut engine = PhysicsEngine::new(nodes.clone(), Vec::new(), None).unwrap();
+        assert_eq!(
+            engine.add_constraint(Constraint::AlignHorizontal(ids(&["a", "c"]))),
+            Err(PhysicsError::UnknownNode("c".to_string()))
+        );
+        assert!(engine.constraints.is_empty());
+
+        engine.add_constraint(Constraint::AlignHorizontal(ids(&["a", "b"]))).unwrap();
+        assert_eq!(
+            engine.set_graph(nodes[..1].to_vec(), Vec::new()),
+            Err(PhysicsError::UnknownNode("b".to_string()))
+        );
+        assert_eq!(engine.nodes.len(), 2);
+
+        // The solver still works on the old graph
+        engine.nodes[0].y = 0.0;
+        engine.nodes[1].y = 20.0;
+        engine.constraints.project(&mut engine.nodes);
+        assert_eq!((engine.nodes[0].y, engine.nodes[1].y), (10.0, 10.0));
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+
+// Errors reported by the physics engine
+#[derive(Clone, Debug, PartialEq)]
+pub enum PhysicsError {
+    // No force is registered under this name
+    UnknownForce(String),
+    // A force is already registered under this name
+    DuplicateForce(String),
+    // Two nodes share this id
+    DuplicateNode(String),
+    // The link at this index refers to a node id that doesn't exist
+    DanglingLink { link: usize, node: String },
+    // The link at this index connects a node to itself
+    SelfLoop { link: usize, node: String },
+    // A constraint refers to a node id that doesn't exist
+    UnknownNode(String),
+    // A file couldn't be read or written
+    Io { path: String, message: String },
+    // Input in the given format couldn't be parsed
+    Parse { format: &'static str, message: String },
+    // A node's position or velocity stopped being finite during this tick
+    NonFinite { tick: usize, node: String },
+}
+
+impl fmt::Display for PhysicsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhysicsError::UnknownForce(name) => write!(f, "no force named \"{}\"", name),
+            PhysicsError::DuplicateForce(name) => write!(f, "a force named \"{}\" already exists", name),
+            PhysicsError::DuplicateNode(id) => write!(f, "duplicate node id \"{}\"", id),
+            PhysicsError::DanglingLink { link, node } => {
+                write!(f, "link {} refers to unknown node \"{}\"", link, node)
+            }
+            PhysicsError::SelfLoop { link, node } => {
+                write!(f, "link {} connects node \"{}\" to itself", link, node)
+            }
+            PhysicsError::UnknownNode(id) => write!(f, "unknown node \"{}\"", id),
+            PhysicsError::Io { path, message } => write!(f, "{}: {}", path, message),
+            PhysicsError::Parse { format, message } => write!(f, "invalid {}: {}", format, message),
+            PhysicsError::NonFinite { tick, node } => {
+                write!(f, "node \"{}\" has a non-finite position or velocity after tick {}", node, tick)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PhysicsError {}
@@ -0,0 +1,146 @@
+use super::Force;
+use crate::layout;
+use crate::physics_engine::{Link, Node};
+use rand::RngCore;
+use std::collections::HashMap;
+
+// Placement of the anchors of categories without a user-supplied anchor
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AnchorLayout {
+    // Evenly spaced on a circle around the canvas center
+    #[default]
+    Circle,
+    // Row-major grid over the canvas
+    Grid,
+}
+
+// Anchor of one category as it stands during the simulation
+#[derive(Clone, Debug)]
+struct Group {
+    category: String,
+    x: f64,
+    y: f64,
+    strength: f64,
+}
+
+// Pulls the nodes of each category toward that category's anchor point
+#[derive(Clone, Debug)]
+pub struct CategoryGroupForce {
+    // User-supplied anchors; every other category found in the graph gets
+    // one from `layout`
+    pub anchors: HashMap<String, (f64, f64)>,
+    pub layout: AnchorLayout,
+    // Canvas the automatic anchors are laid out over
+    pub width: f64,
+    pub height: f64,
+    pub strength: f64,
+    // Per-category overrides of `strength`
+    pub category_strength: HashMap<String, f64>,
+    // Fraction of the way each anchor moves toward its group's centroid per
+    // tick; 0 keeps anchors fixed
+    pub drift: f64,
+    groups: Vec<Group>,
+    // Group of each node
+    membership: Vec<usize>,
+}
+
+impl CategoryGroupForce {
+    // Automatic anchors for a width x height canvas
+    pub fn new(width: f64, height: f64) -> Self {
+        CategoryGroupForce {
+            anchors: HashMap::new(),
+            layout: AnchorLayout::Circle,
+            width,
+            height,
+            strength: 0.01,
+            category_strength: HashMap::new(),
+            drift: 0.0,
+            groups: Vec::new(),
+            membership: Vec::new(),
+        }
+    }
+
+    // Current anchor of a category, including drift
+    pub fn anchor(&self, category: &str) -> Option<(f64, f64)> {
+        self.groups
+            .iter()
+            .find(|group| group.category == category)
+            .map(|group| (group.x, group.y))
+    }
+
+    fn automatic_anchors(&self, count: usize) -> Vec<(f64, f64)> {
+        match (self.layout, count) {
+            (_, 1) => vec![(self.width / 2.0, self.height / 2.0)],
+            (AnchorLayout::Circle, _) => layout::circular(count, self.width, self.height),
+            (AnchorLayout::Grid, _) => layout::grid(count, self.width, self.height),
+        }
+    }
+}
+
+impl Force for CategoryGroupForce {
+    fn initialize(&mut self, nodes: &[Node], _links: &[Link], _endpoints: &[(usize, usize)]) {
+        // Categories in order of first appearance, so placement is deterministic
+        let mut categories: Vec<&str> = Vec::new();
+        for node in nodes {
+            if !categories.contains(&node.category.as_str()) {
+                categories.push(&node.category);
+            }
+        }
+
+        let automatic: Vec<&str> = categories
+            .iter()
+            .copied()
+            .filter(|category| !self.anchors.contains_key(*category))
+            .collect();
+        let mut placed = automatic.iter().copied().zip(self.automatic_anchors(automatic.len()));
+
+        let groups: Vec<Group> = categories
+            .iter()
+            .map(|&category| {
+                // A graph change keeps anchors that have already drifted
+                let (x, y) = match (self.anchor(category), self.anchors.get(category)) {
+                    (Some(current), _) => current,
+                    (None, Some(&anchor)) => anchor,
+                    (None, None) => placed.find(|(c, _)| *c == category).map(|(_, p)| p).unwrap_or_default(),
+                };
+                let strength = self.category_strength.get(category).copied().unwrap_or(self.strength);
+                Group { category: category.to_string(), x, y, strength }
+            })
+            .collect();
+
+        self.membership = nodes
+            .iter()
+            .map(|node| categories.iter().position(|&c| c == node.category).unwrap_or(0))
+            .collect();
+        self.groups = groups;
+    }
+
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, _rng: &mut dyn RngCore) {
+        for (node, &group) in nodes.iter_mut().zip(&self.membership) {
+            let group = &self.groups[group];
+            node.vx += (group.x - node.x) * group.strength * alpha;
+            node.vy += (group.y - node.y) * group.strength * alpha;
+        }
+    }
+
+    // Anchors drift once per tick, after the nodes have moved: `apply` may be
+    // evaluated several times per tick, on trial states, by the integrator
+    fn adjust_positions(&mut self, nodes: &mut [Node]) {
+        if self.drift <= 0.0 {
+            return;
+        }
+
+        let mut sums = vec![(0.0, 0.0, 0usize); self.groups.len()];
+        for (node, &group) in nodes.iter().zip(&self.membership) {
+            sums[group].0 += node.x;
+            sums[group].1 += node.y;
+            sums[group].2 += 1;
+        }
+        for (group, (sx, sy, count)) in self.groups.iter_mut().zip(sums) {
+            if count > 0 {
+                group.x += (sx / count as f64 - group.x) * self.drift;
+                group.y += (sy / count as f64 - group.y) * self.drift;
+            }
+        }
+    }
+}
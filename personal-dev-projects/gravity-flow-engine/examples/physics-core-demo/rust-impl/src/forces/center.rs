@@ -0,0 +1,62 @@
+use super::Force;
+use crate::physics_engine::Node;
+use rand::RngCore;
+
+// Translates the whole system so its center sits on a fixed point, like
+// d3.forceCenter. Relative positions are untouched.
+#[derive(Clone, Debug)]
+pub struct CenterForce {
+    pub x: f64,
+    pub y: f64,
+    // Fraction of the offset removed per tick
+    pub strength: f64,
+    // Center on the center of mass instead of the plain centroid
+    pub mass_weighted: bool,
+}
+
+impl CenterForce {
+    pub fn new(x: f64, y: f64) -> Self {
+        CenterForce {
+            x,
+            y,
+            strength: 1.0,
+            mass_weighted: false,
+        }
+    }
+}
+
+impl Force for CenterForce {
+    fn apply(&mut self, _nodes: &mut [Node], _alpha: f64, _rng: &mut dyn RngCore) {}
+
+    fn adjust_positions(&mut self, nodes: &mut [Node]) {
+        let (mut sx, mut sy, mut total) = (0.0, 0.0, 0.0);
+        for node in nodes.iter() {
+            let weight = if self.mass_weighted {
+                1.0 / node.physical_properties.inverse_mass()
+            } else {
+                1.0
+            };
+            sx += node.x * weight;
+            sy += node.y * weight;
+            total += weight;
+        }
+        if total == 0.0 {
+            return;
+        }
+
+        let dx = (sx / total - self.x) * self.strength;
+        let dy = (sy / total - self.y) * self.strength;
+        // Fixed and pinned nodes stay where they are
+        for node in nodes {
+            if node.physical_properties.fixed {
+                continue;
+            }
+            if node.fx.is_none() {
+                node.x -= dx;
+            }
+            if node.fy.is_none() {
+                node.y -= dy;
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use super::{jiggle, movable, Force};
+use crate::physics_engine::Node;
+use crate::spatial_grid::SpatialGrid;
+use rand::RngCore;
+
+// Keeps nodes from overlapping, treating each one as a disc of its radius
+#[derive(Clone, Debug)]
+pub struct CollisionForce {
+    // Fraction of the overlap resolved per pass
+    pub strength: f64,
+    // Relaxation passes per tick
+    pub iterations: usize,
+}
+
+impl Default for CollisionForce {
+    fn default() -> Self {
+        CollisionForce {
+            strength: 1.0,
+            iterations: 1,
+        }
+    }
+}
+
+impl Force for CollisionForce {
+    // Overlaps are resolved by the exact correction, whatever the masses
+    fn mass_scaled(&self) -> bool {
+        false
+    }
+    
+    fn apply(&mut self, nodes: &mut [Node], _alpha: f64, rng: &mut dyn RngCore) {
+        let max_radius = nodes
+            .iter()
+            .map(|node| node.physical_properties.radius)
+            .fold(0.0, f64::max);
+        
+        if max_radius <= 0.0 {
+            return;
+        }
+        
+        // Relax overlaps d3-style: each pass works on the positions the nodes
+        // are about to reach (x + vx) and splits the correction by radius²
+        for _ in 0..self.iterations {
+            let predicted: Vec<(f64, f64)> = nodes
+                .iter()
+                .map(|node| (node.x + node.vx, node.y + node.vy))
+                .collect();
+            
+            // Overlapping pairs are closer than the largest diameter, so they
+            // always share or neighbour a grid cell of that size
+            let grid = SpatialGrid::build(&predicted, 2.0 * max_radius);
+            
+            for (i, &(x_i, y_i)) in predicted.iter().enumerate() {
+                let radius_i = nodes[i].physical_properties.radius;
+                
+                for j in grid.neighbors(x_i, y_i) {
+                    if j <= i {
+                        continue;
+                    }
+                    
+                    let node_j = &nodes[j];
+                    let radius_j = node_j.physical_properties.radius;
+                    let min_distance = radius_i + radius_j;
+                    
+                    // Calculate distance vector between predicted positions
+                    let mut dx = x_i - (node_j.x + node_j.vx);
+                    let mut dy = y_i - (node_j.y + node_j.vy);
+                    
+                    // Skip if nodes don't overlap
+                    if dx * dx + dy * dy >= min_distance * min_distance {
+                        continue;
+                    }
+                    
+                    // Separate coincident nodes randomly
+                    if dx == 0.0 && dy == 0.0 {
+                        dx = jiggle(rng);
+                        dy = jiggle(rng);
+                    }
+                    let distance_squared = dx * dx + dy * dy;
+                    
+                    // Push apart, the larger node moving less. A node that
+                    // can't move along an axis leaves the whole correction
+                    // to the other one.
+                    let distance = distance_squared.sqrt();
+                    let correction = (min_distance - distance) / distance * self.strength;
+                    let share_i = radius_j * radius_j / (radius_i * radius_i + radius_j * radius_j);
+                    
+                    let shares = |pin_i: Option<f64>, pin_j: Option<f64>| {
+                        let weight_i = movable(&nodes[i], pin_i) * share_i;
+                        let weight_j = movable(&nodes[j], pin_j) * (1.0 - share_i);
+                        let total = weight_i + weight_j;
+                        if total > 0.0 {
+                            (weight_i / total, weight_j / total)
+                        } else {
+                            (0.0, 0.0)
+                        }
+                    };
+                    let (x_i, x_j) = shares(nodes[i].fx, nodes[j].fx);
+                    let (y_i, y_j) = shares(nodes[i].fy, nodes[j].fy);
+                    
+                    nodes[i].vx += dx * correction * x_i;
+                    nodes[i].vy += dy * correction * y_i;
+                    nodes[j].vx -= dx * correction * x_j;
+                    nodes[j].vy -= dy * correction * y_j;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use super::Force;
+use crate::physics_engine::{Link, Node};
+use rand::RngCore;
+
+// Direction in which one-way links should point on screen
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FlowDirection {
+    // Sources above targets
+    #[default]
+    Down,
+    Up,
+    // Sources left of targets
+    Right,
+    Left,
+}
+
+impl FlowDirection {
+    // Screen-space unit vector from source to target (y grows downward)
+    fn unit(self) -> (f64, f64) {
+        match self {
+            FlowDirection::Down => (0.0, 1.0),
+            FlowDirection::Up => (0.0, -1.0),
+            FlowDirection::Right => (1.0, 0.0),
+            FlowDirection::Left => (-1.0, 0.0),
+        }
+    }
+}
+
+// Pushes the ends of one-way links apart along the flow direction until the
+// target is at least `separation` past its source
+#[derive(Clone, Debug)]
+pub struct DirectionalForce {
+    pub direction: FlowDirection,
+    pub separation: f64,
+    pub strength: f64,
+    // Also orient bidirectional links, from source to target as listed
+    pub include_bidirectional: bool,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Default for DirectionalForce {
+    fn default() -> Self {
+        DirectionalForce {
+            direction: FlowDirection::Down,
+            separation: 60.0,
+            strength: 0.1,
+            include_bidirectional: false,
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl Force for DirectionalForce {
+    fn initialize(&mut self, _nodes: &[Node], links: &[Link], endpoints: &[(usize, usize)]) {
+        self.edges = links
+            .iter()
+            .zip(endpoints)
+            .filter(|(link, _)| self.include_bidirectional || !link.physical_properties.bidirectional)
+            .map(|(_, &edge)| edge)
+            .collect();
+    }
+
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, _rng: &mut dyn RngCore) {
+        let (ux, uy) = self.direction.unit();
+
+        for &(source, target) in &self.edges {
+            // Signed distance from source to target along the flow direction
+            let gap = (nodes[target].x - nodes[source].x) * ux + (nodes[target].y - nodes[source].y) * uy;
+            if gap >= self.separation {
+                continue;
+            }
+
+            // Both ends share the correction
+            let push = (self.separation - gap) * self.strength * alpha / 2.0;
+            nodes[source].vx -= ux * push;
+            nodes[source].vy -= uy * push;
+            nodes[target].vx += ux * push;
+            nodes[target].vy += uy * push;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::{LinkPhysicalProperties, PhysicalProperties, RelationshipType, SpringModel};
+    use rand::rngs::mock::StepRng;
+    use std::collections::BTreeMap;
+
+    fn node(x: f64, y: f64) -> Node {
+        Node {
+            id: String::new(),
+            label: String::new(),
+            category: String::new(),
+            physical_properties: PhysicalProperties {
+                mass: 1.0,
+                charge: 0.0,
+                friction: 0.0,
+                radius: 1.0,
+                fixed: false,
+                initial_position: None,
+            },
+            metadata: BTreeMap::new(),
+            x,
+            y,
+            vx: 0.0,
+            vy: 0.0,
+            fx: None,
+            fy: None,
+        }
+    }
+
+    fn link(bidirectional: bool) -> Link {
+        Link {
+            source: "a".to_string(),
+            target: "b".to_string(),
+            relationship_type: RelationshipType::Causal,
+            physical_properties: LinkPhysicalProperties {
+                stiffness: 0.1,
+                length: 50.0,
+                elasticity: 0.0,
+                spring_model: SpringModel::Linear,
+                bidirectional,
+            },
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    // Velocities of the source and target after one application
+    fn push(force: DirectionalForce, target: (f64, f64), bidirectional: bool) -> [(f64, f64); 2] {
+        let mut force = force;
+        let mut nodes = [node(0.0, 0.0), node(target.0, target.1)];
+        force.initialize(&nodes, &[link(bidirectional)], &[(0, 1)]);
+        force.apply(&mut nodes, 1.0, &mut StepRng::new(0, 1));
+        nodes.map(|node| (node.vx, node.vy))
+    }
+
+    #[test]
+    fn one_way_links_are_pushed_apart_along_the_flow() {
+        // 50 short of the separation, split between both ends
+        let force = DirectionalForce { separation: 60.0, strength: 0.1, ..Default::default() };
+        assert_eq!(push(force.clone(), (0.0, 10.0), false), [(0.0, -2.5), (0.0, 2.5)]);
+
+        let right = DirectionalForce { direction: FlowDirection::Right, ..force.clone() };
+        assert_eq!(push(right, (0.0, 10.0), false), [(-3.0, 0.0), (3.0, 0.0)]);
+
+        // Already far enough along the flow
+        assert_eq!(push(force, (0.0, 80.0), false), [(0.0, 0.0); 2]);
+    }
+
+    #[test]
+    fn bidirectional_links_are_oriented_only_on_request() {
+        let force = DirectionalForce { separation: 60.0, strength: 0.1, ..Default::default() };
+        assert_eq!(push(force.clone(), (0.0, 10.0), true), [(0.0, 0.0); 2]);
+
+        let all = DirectionalForce { include_bidirectional: true, ..force };
+        assert_eq!(push(all, (0.0, 10.0), true), [(0.0, -2.5), (0.0, 2.5)]);
+    }
+}
@@ -0,0 +1,26 @@
+use super::Force;
+use crate::physics_engine::Node;
+use rand::RngCore;
+
+// Spring pulling every node toward a fixed point
+#[derive(Clone, Debug)]
+pub struct GravityForce {
+    pub x: f64,
+    pub y: f64,
+    pub strength: f64,
+}
+
+impl GravityForce {
+    pub fn new(x: f64, y: f64) -> Self {
+        GravityForce { x, y, strength: 0.1 }
+    }
+}
+
+impl Force for GravityForce {
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, _rng: &mut dyn RngCore) {
+        for node in nodes {
+            node.vx += (self.x - node.x) * self.strength * alpha;
+            node.vy += (self.y - node.y) * self.strength * alpha;
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use super::{jiggle, movable, Force};
+use crate::physics_engine::{Link, Node, SpringModel};
+use rand::RngCore;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LinkMode {
+    // Hooke spring on current positions, split equally between both ends
+    #[default]
+    Hooke,
+    // d3.forceLink: correction on predicted positions (x + vx), split by
+    // degree bias and, as in d3, not divided by mass
+    D3,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LinkStrength {
+    // LinkPhysicalProperties::stiffness of each link
+    #[default]
+    Stiffness,
+    // d3's default, 1 / min(degree(source), degree(target))
+    Degree,
+    Constant(f64),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LinkDirection {
+    // Every link pulls on both ends
+    #[default]
+    Symmetric,
+    // One-way links move only their target, so sources act as anchors
+    TargetOnly,
+}
+
+// Passes over the MaxStretch links per tick; links sharing a node can pull
+// each other past their limit again
+const MAX_STRETCH_PASSES: usize = 10;
+
+// Resolved link with everything the force needs per tick
+#[derive(Clone, Debug)]
+struct Spring {
+    source: usize,
+    target: usize,
+    strength: f64,
+    length: f64,
+    model: SpringModel,
+    elasticity: f64,
+    // Share of the correction taken by the target
+    bias: f64,
+    // The source is not moved by this spring
+    one_way: bool,
+}
+
+impl Spring {
+    // Signed spring force at the given distance; positive pulls the ends together
+    fn tension(&self, distance: f64) -> f64 {
+        let softening = if distance > self.length { 1.0 + self.elasticity.max(0.0) } else { 1.0 };
+        match self.model {
+            SpringModel::Linear | SpringModel::MaxStretch => self.strength * (distance - self.length),
+            SpringModel::Asymmetric => self.strength * (distance - self.length) / softening,
+            SpringModel::Logarithmic => {
+                // Keep the logarithm finite for nearly coincident ends
+                let ratio = (distance / self.length).max(0.01);
+                self.strength * self.length * ratio.ln() / softening
+            }
+        }
+    }
+
+    fn max_length(&self) -> Option<f64> {
+        match self.model {
+            SpringModel::MaxStretch => Some(self.length * (1.0 + self.elasticity.max(0.0))),
+            _ => None,
+        }
+    }
+}
+
+// Spring along every link
+#[derive(Clone, Debug)]
+pub struct LinkForce {
+    pub mode: LinkMode,
+    pub strength: LinkStrength,
+    pub direction: LinkDirection,
+    // Passes over all links per tick
+    pub iterations: usize,
+    springs: Vec<Spring>,
+}
+
+impl Default for LinkForce {
+    fn default() -> Self {
+        LinkForce {
+            mode: LinkMode::Hooke,
+            strength: LinkStrength::Stiffness,
+            direction: LinkDirection::Symmetric,
+            iterations: 1,
+            springs: Vec::new(),
+        }
+    }
+}
+
+impl LinkForce {
+    // Same semantics as d3.forceLink with its default strength
+    pub fn d3() -> Self {
+        LinkForce {
+            mode: LinkMode::D3,
+            strength: LinkStrength::Degree,
+            ..Default::default()
+        }
+    }
+}
+
+impl Force for LinkForce {
+    fn initialize(&mut self, nodes: &[Node], links: &[Link], endpoints: &[(usize, usize)]) {
+        let mut degree = vec![0usize; nodes.len()];
+        for &(source, target) in endpoints {
+            degree[source] += 1;
+            degree[target] += 1;
+        }
+
+        self.springs = links
+            .iter()
+            .zip(endpoints)
+            .map(|(link, &(source, target))| {
+                let properties = &link.physical_properties;
+                let strength = match self.strength {
+                    LinkStrength::Stiffness => properties.stiffness,
+                    LinkStrength::Degree => 1.0 / degree[source].min(degree[target]) as f64,
+                    LinkStrength::Constant(strength) => strength,
+                };
+                let one_way = self.direction == LinkDirection::TargetOnly && !properties.bidirectional;
+                let bias = if one_way {
+                    1.0
+                } else {
+                    degree[source] as f64 / (degree[source] + degree[target]) as f64
+                };
+
+                Spring {
+                    source,
+                    target,
+                    strength,
+                    length: properties.length,
+                    model: properties.spring_model,
+                    elasticity: properties.elasticity,
+                    bias,
+                    one_way,
+                }
+            })
+            .collect();
+    }
+
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore) {
+        for _ in 0..self.iterations {
+            match self.mode {
+                LinkMode::Hooke => self.apply_hooke(nodes, alpha, rng),
+                LinkMode::D3 => self.apply_d3(nodes, alpha, rng),
+            }
+        }
+    }
+
+    // Rigid limit for MaxStretch links. Their ends are moved back together
+    // after integration, split by the spring's bias, until no link is longer
+    // than its maximum length; fixed and pinned ends stay where they are.
+    fn adjust_positions(&mut self, nodes: &mut [Node]) {
+        for _ in 0..MAX_STRETCH_PASSES {
+            let mut stretched = false;
+            for spring in &self.springs {
+                let Some(max_length) = spring.max_length() else {
+                    continue;
+                };
+
+                let (source, target) = (&nodes[spring.source], &nodes[spring.target]);
+                let (dx, dy) = (target.x - source.x, target.y - source.y);
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance <= max_length {
+                    continue;
+                }
+                stretched = true;
+
+                let excess = (distance - max_length) / distance;
+                let target_x = movable(target, target.fx) * spring.bias;
+                let target_y = movable(target, target.fy) * spring.bias;
+                let source_x = movable(source, source.fx) * (1.0 - spring.bias);
+                let source_y = movable(source, source.fy) * (1.0 - spring.bias);
+                if target_x + source_x > 0.0 {
+                    let shift = dx * excess / (target_x + source_x);
+                    nodes[spring.target].x -= shift * target_x;
+                    nodes[spring.source].x += shift * source_x;
+                }
+                if target_y + source_y > 0.0 {
+                    let shift = dy * excess / (target_y + source_y);
+                    nodes[spring.target].y -= shift * target_y;
+                    nodes[spring.source].y += shift * source_y;
+                }
+            }
+            if !stretched {
+                break;
+            }
+        }
+    }
+
+    // d3-style links relax positions directly, as d3 does, independent of mass
+    fn mass_scaled(&self) -> bool {
+        self.mode != LinkMode::D3
+    }
+}
+
+impl LinkForce {
+    fn apply_hooke(&self, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore) {
+        for spring in &self.springs {
+            let (source_idx, target_idx) = (spring.source, spring.target);
+            let (x1, y1, x2, y2) = {
+                let source = &nodes[source_idx];
+                let target = &nodes[target_idx];
+                (source.x, source.y, target.x, target.y)
+            };
+
+            // Calculate distance vector, separating coincident nodes randomly
+            let (mut dx, mut dy) = (x2 - x1, y2 - y1);
+            if dx == 0.0 && dy == 0.0 {
+                dx = jiggle(rng);
+                dy = jiggle(rng);
+            }
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            // Calculate spring force from the link's spring model
+            let spring_force = spring.tension(distance);
+
+            // Apply force proportional to displacement
+            let unit_x = dx / distance;
+            let unit_y = dy / distance;
+
+            let fx = spring_force * unit_x * alpha;
+            let fy = spring_force * unit_y * alpha;
+
+            // Apply forces to source and target
+            if !spring.one_way {
+                nodes[source_idx].vx += fx;
+                nodes[source_idx].vy += fy;
+            }
+            nodes[target_idx].vx -= fx;
+            nodes[target_idx].vy -= fy;
+        }
+    }
+
+    fn apply_d3(&self, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore) {
+        for spring in &self.springs {
+            let (source, target) = (&nodes[spring.source], &nodes[spring.target]);
+
+            // Distance between the positions both ends are about to reach
+            let (mut dx, mut dy) = (
+                target.x + target.vx - source.x - source.vx,
+                target.y + target.vy - source.y - source.vy,
+            );
+            if dx == 0.0 && dy == 0.0 {
+                dx = jiggle(rng);
+                dy = jiggle(rng);
+            }
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            // Fraction of the length error corrected this pass
+            let correction = spring.tension(distance) / distance * alpha;
+            dx *= correction;
+            dy *= correction;
+
+            // Well-connected ends move less
+            nodes[spring.target].vx -= dx * spring.bias;
+            nodes[spring.target].vy -= dy * spring.bias;
+            nodes[spring.source].vx += dx * (1.0 - spring.bias);
+            nodes[spring.source].vy += dy * (1.0 - spring.bias);
+        }
+    }
+}
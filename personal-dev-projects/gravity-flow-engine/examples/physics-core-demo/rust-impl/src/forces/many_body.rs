@@ -0,0 +1,260 @@
+use super::{pairwise, Force};
+use crate::physics_engine::Node;
+use crate::quadtree::QuadTree;
+use rand::RngCore;
+
+// Magnitude of an interaction: strength · |charge_i · charge_j| / distance^exponent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChargeLaw {
+    pub strength: f64,
+    pub exponent: f64,
+}
+
+impl ChargeLaw {
+    pub fn inverse_square(strength: f64) -> Self {
+        ChargeLaw { strength, exponent: 2.0 }
+    }
+
+    fn magnitude(&self, charge: f64, distance_squared: f64) -> f64 {
+        let falloff = if self.exponent == 2.0 {
+            distance_squared
+        } else {
+            distance_squared.powf(self.exponent / 2.0)
+        };
+        self.strength * charge.abs() / falloff
+    }
+}
+
+// Charge interaction between every pair of nodes: like charges repel
+// following `repulsion`, opposite charges attract following `attraction`
+#[derive(Clone, Debug)]
+pub struct ManyBodyForce {
+    pub repulsion: ChargeLaw,
+    pub attraction: ChargeLaw,
+    // Barnes-Hut accuracy; None runs the exact O(n²) pass
+    pub theta: Option<f64>,
+    // Closer pairs interact as if they were this far apart. With charges in
+    // the hundreds, the default of 40 (two node diameters) keeps nodes that
+    // start on the same point from being flung off the canvas.
+    pub distance_min: f64,
+    // Pairs further apart don't interact
+    pub distance_max: Option<f64>,
+    // Plummer softening length ε: distances are taken as sqrt(d² + ε²)
+    pub softening: f64,
+}
+
+impl Default for ManyBodyForce {
+    fn default() -> Self {
+        ManyBodyForce {
+            repulsion: ChargeLaw::inverse_square(30.0),
+            attraction: ChargeLaw::inverse_square(30.0),
+            theta: Some(0.9),
+            distance_min: 40.0,
+            distance_max: None,
+            softening: 0.0,
+        }
+    }
+}
+
+impl Force for ManyBodyForce {
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore) {
+        match self.theta {
+            Some(theta) => self.apply_barnes_hut(nodes, alpha, theta, rng),
+            None => self.apply_exact(nodes, alpha, rng),
+        }
+    }
+}
+
+impl ManyBodyForce {
+    // Pull between two charges, positive toward each other
+    fn pull(&self, charge_i: f64, charge_j: f64, distance_squared: f64) -> f64 {
+        if self.distance_max.is_some_and(|max| distance_squared > max * max) {
+            return 0.0;
+        }
+        let distance_squared = distance_squared.max(self.distance_min * self.distance_min) + self.softening * self.softening;
+
+        let charge = charge_i * charge_j;
+        if charge > 0.0 {
+            -self.repulsion.magnitude(charge, distance_squared)
+        } else {
+            self.attraction.magnitude(charge, distance_squared)
+        }
+    }
+
+    pub fn apply_exact(&self, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore) {
+        let positions: Vec<(f64, f64)> = nodes.iter().map(|node| (node.x, node.y)).collect();
+        let charges: Vec<f64> = nodes.iter().map(|node| node.physical_properties.charge).collect();
+
+        let field = pairwise::exact(&positions, &charges, &|qi, qj, d2| self.pull(qi, qj, d2), rng);
+        for (node, (fx, fy)) in nodes.iter_mut().zip(field) {
+            node.vx += fx * alpha;
+            node.vy += fy * alpha;
+        }
+    }
+
+    pub fn apply_barnes_hut(&self, nodes: &mut [Node], alpha: f64, theta: f64, rng: &mut dyn RngCore) {
+        // Rebuild the tree every tick since every node may have moved
+        let positions: Vec<(f64, f64)> = nodes.iter().map(|node| (node.x, node.y)).collect();
+        let charges: Vec<f64> = nodes.iter().map(|node| node.physical_properties.charge).collect();
+        let tree = QuadTree::build(&positions);
+
+        // Positive and negative charges are aggregated separately, so a cell
+        // is never approximated by a net charge that mixes both laws
+        let pull = |qi, qj, d2| self.pull(qi, qj, d2);
+        for sign in [1.0, -1.0] {
+            let sources: Vec<f64> = charges.iter().map(|&q| if q * sign > 0.0 { q } else { 0.0 }).collect();
+            let field = pairwise::barnes_hut(&tree, &positions, &charges, &sources, theta, &pull, rng);
+            for (node, (fx, fy)) in nodes.iter_mut().zip(field) {
+                node.vx += fx * alpha;
+                node.vy += fy * alpha;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::{PhysicalProperties, PhysicsEngine, PhysicsOptions, Position};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Nodes scattered over a 1000×1000 square with charges of both signs
+    fn mixed_charges(count: usize, seed: u64) -> Vec<Node> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|i| Node {
+                id: i.to_string(),
+                label: String::new(),
+                category: String::new(),
+                physical_properties: PhysicalProperties {
+                    mass: 1.0,
+                    charge: if rng.gen() { -100.0 } else { 60.0 } * rng.gen::<f64>(),
+                    friction: 0.0,
+                    radius: 1.0,
+                    fixed: false,
+                    initial_position: None,
+                },
+                metadata: Default::default(),
+                x: rng.gen::<f64>() * 1000.0,
+                y: rng.gen::<f64>() * 1000.0,
+                vx: 0.0,
+                vy: 0.0,
+                fx: None,
+                fy: None,
+            })
+            .collect()
+    }
+
+    // Total error relative to the total magnitude of the exact field, and the
+    // worst node's error relative to the mean magnitude. Per-node relative
+    // errors aren't meaningful: nodes whose pulls nearly cancel out have an
+    // arbitrarily small exact force.
+    fn errors(force: &ManyBodyForce, theta: f64) -> (f64, f64) {
+        let mut exact = mixed_charges(500, 7);
+        let mut approximate = exact.clone();
+        let mut rng = StdRng::seed_from_u64(0);
+        force.apply_exact(&mut exact, 1.0, &mut rng);
+        force.apply_barnes_hut(&mut approximate, 1.0, theta, &mut rng);
+
+        let (mut error, mut magnitude, mut worst) = (0.0, 0.0, 0.0_f64);
+        for (a, b) in exact.iter().zip(&approximate) {
+            let e = (a.vx - b.vx).hypot(a.vy - b.vy);
+            error += e;
+            magnitude += a.vx.hypot(a.vy);
+            worst = worst.max(e);
+        }
+        (error / magnitude, worst / (magnitude / exact.len() as f64))
+    }
+
+    // The plain inverse-square law. Nodes of the fixture are closer together
+    // than the default distance_min, whose flat core a cell's centroid
+    // approximates less well.
+    fn point_charges() -> ManyBodyForce {
+        ManyBodyForce {
+            distance_min: 1.0,
+            ..ManyBodyForce::default()
+        }
+    }
+
+    #[test]
+    fn barnes_hut_without_approximation_is_exact() {
+        let (total, worst) = errors(&ManyBodyForce::default(), 0.0);
+        assert!(total < 1e-12 && worst < 1e-12, "total {} worst {}", total, worst);
+    }
+
+    #[test]
+    fn barnes_hut_stays_close_to_the_exact_field() {
+        let force = point_charges();
+        let (total, worst) = errors(&force, 0.5);
+        assert!(total < 0.01 && worst < 0.1, "theta 0.5: total {} worst {}", total, worst);
+        let (total, worst) = errors(&force, 0.9);
+        assert!(total < 0.02 && worst < 0.25, "theta 0.9: total {} worst {}", total, worst);
+
+        // Pairs inside distance_min
+        let (total, _) = errors(&ManyBodyForce::default(), 0.5);
+        assert!(total < 0.015, "default distance_min: total {}", total);
+    }
+
+    #[test]
+    fn barnes_hut_respects_distance_max_and_softening() {
+        let force = ManyBodyForce {
+            distance_max: Some(200.0),
+            softening: 5.0,
+            ..point_charges()
+        };
+        let (total, _) = errors(&force, 0.5);
+        assert!(total < 0.02, "total {}", total);
+    }
+
+    // Nodes like the demo network's, all starting on the same point
+    fn coincident(count: usize, x: f64, y: f64) -> Vec<Node> {
+        (0..count)
+            .map(|i| Node {
+                id: i.to_string(),
+                label: String::new(),
+                category: String::new(),
+                physical_properties: PhysicalProperties {
+                    mass: 5.0,
+                    charge: -150.0,
+                    friction: 0.2,
+                    radius: 15.0,
+                    fixed: false,
+                    initial_position: Some(Position { x, y }),
+                },
+                metadata: Default::default(),
+                x: 0.0,
+                y: 0.0,
+                vx: 0.0,
+                vy: 0.0,
+                fx: None,
+                fy: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn coincident_nodes_separate_without_leaving_the_canvas() {
+        // Fifty nodes this strongly charged settle about 700 from the center,
+        // wherever they start, so the canvas is made big enough to hold them
+        for seed in 0..3 {
+            let options = PhysicsOptions { width: 2000.0, height: 2000.0, seed: Some(seed), ..PhysicsOptions::default() };
+            let mut engine = PhysicsEngine::new(coincident(50, 1000.0, 1000.0), Vec::new(), Some(options)).unwrap();
+            engine.initialize_standard_forces();
+
+            for _ in 0..300 {
+                engine.tick().unwrap();
+                for node in &engine.nodes {
+                    let offset = (node.x - 1000.0).abs().max((node.y - 1000.0).abs());
+                    assert!(offset < 1000.0, "seed {}: a node got {} from the center", seed, offset);
+                }
+            }
+            for (i, a) in engine.nodes.iter().enumerate() {
+                for b in &engine.nodes[i + 1..] {
+                    let distance = (a.x - b.x).hypot(a.y - b.y);
+                    assert!(distance > 30.0, "seed {}: two nodes are only {} apart", seed, distance);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// Forces applied by the physics engine on every tick
+
+mod category_group;
+mod center;
+mod collision;
+mod directional;
+mod gravity;
+mod link;
+mod many_body;
+mod newtonian;
+mod pairwise;
+mod position;
+mod registry;
+
+pub use category_group::{AnchorLayout, CategoryGroupForce};
+pub use center::CenterForce;
+pub use collision::CollisionForce;
+pub use directional::{DirectionalForce, FlowDirection};
+pub use gravity::GravityForce;
+pub use link::{LinkDirection, LinkForce, LinkMode, LinkStrength};
+pub use many_body::{ChargeLaw, ManyBodyForce};
+pub use newtonian::NewtonianGravityForce;
+pub use position::{constant, NodeAccessor, PositionXForce, PositionYForce, RadialForce};
+pub use registry::ForceRegistry;
+
+use crate::physics_engine::{Link, Node};
+use rand::{Rng, RngCore};
+
+// A force holds its own configuration and any data it caches about the graph.
+// `initialize` runs when the force is added to an engine and whenever the
+// graph changes, with the (source, target) node indices of every link, which
+// the engine has already validated; `apply` runs once per tick and adjusts node velocities.
+// `adjust_positions` runs once per tick after integration, for forces that
+// move nodes directly or update their own state once per tick. Integrators
+// may evaluate `apply` several times per tick on trial states, so it must
+// not change the force's state.
+// The velocity change made by `apply` is a force and is divided by each
+// node's mass, unless `mass_scaled` returns false: forces that relax
+// positions (collision, d3-style links) compute the exact correction they
+// want and opt out.
+// Any randomness must come from `rng`, the engine's seeded generator.
+pub trait Force {
+    fn initialize(&mut self, _nodes: &[Node], _links: &[Link], _endpoints: &[(usize, usize)]) {}
+
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore);
+
+    fn mass_scaled(&self) -> bool {
+        true
+    }
+
+    fn adjust_positions(&mut self, _nodes: &mut [Node]) {}
+}
+
+// 1 if the node may be moved along an axis with the given pin, 0 otherwise
+pub(crate) fn movable(node: &Node, pin: Option<f64>) -> f64 {
+    if node.physical_properties.fixed || pin.is_some() {
+        0.0
+    } else {
+        1.0
+    }
+}
+
+// Tiny random offset used to separate coincident nodes, as in d3
+pub fn jiggle(rng: &mut dyn RngCore) -> f64 {
+    (rng.gen::<f64>() - 0.5) * 1e-6
+}
@@ -0,0 +1,121 @@
+use super::{pairwise, Force};
+use crate::physics_engine::Node;
+use crate::quadtree::QuadTree;
+use rand::RngCore;
+
+// Mutual attraction between node masses, G · m_i · m_j / d². The engine
+// divides by each node's mass, so heavy nodes move less.
+#[derive(Clone, Debug)]
+pub struct NewtonianGravityForce {
+    // Gravitational constant G
+    pub constant: f64,
+    // Barnes-Hut accuracy; None runs the exact O(n²) pass
+    pub theta: Option<f64>,
+    // Closer pairs attract as if they were this far apart
+    pub distance_min: f64,
+    // Plummer softening length ε: distances are taken as sqrt(d² + ε²)
+    pub softening: f64,
+}
+
+impl Default for NewtonianGravityForce {
+    fn default() -> Self {
+        NewtonianGravityForce {
+            constant: 1.0,
+            theta: Some(0.9),
+            distance_min: 1.0,
+            softening: 0.0,
+        }
+    }
+}
+
+impl Force for NewtonianGravityForce {
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore) {
+        let positions: Vec<(f64, f64)> = nodes.iter().map(|node| (node.x, node.y)).collect();
+        let masses: Vec<f64> = nodes.iter().map(|node| 1.0 / node.physical_properties.inverse_mass()).collect();
+
+        let pull = |mi: f64, mj: f64, d2: f64| {
+            let d2 = d2.max(self.distance_min * self.distance_min) + self.softening * self.softening;
+            self.constant * mi * mj / d2
+        };
+        let field = match self.theta {
+            Some(theta) => {
+                let tree = QuadTree::build(&positions);
+                pairwise::barnes_hut(&tree, &positions, &masses, &masses, theta, &pull, rng)
+            }
+            None => pairwise::exact(&positions, &masses, &pull, rng),
+        };
+
+        for (node, (fx, fy)) in nodes.iter_mut().zip(field) {
+            node.vx += fx * alpha;
+            node.vy += fy * alpha;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::{PhysicalProperties, PhysicsEngine, PhysicsOptions, Position};
+    use rand::rngs::mock::StepRng;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn node(x: f64, y: f64, mass: f64) -> Node {
+        Node {
+            id: format!("{},{}", x, y),
+            label: String::new(),
+            category: String::new(),
+            physical_properties: PhysicalProperties {
+                mass,
+                charge: 0.0,
+                friction: 0.0,
+                radius: 1.0,
+                fixed: false,
+                initial_position: Some(Position { x, y }),
+            },
+            metadata: Default::default(),
+            x,
+            y,
+            vx: 0.0,
+            vy: 0.0,
+            fx: None,
+            fy: None,
+        }
+    }
+
+    #[test]
+    fn bodies_attract_with_equal_and_opposite_forces() {
+        let force = NewtonianGravityForce { theta: None, ..Default::default() };
+        let mut nodes = [node(0.0, 0.0, 2.0), node(10.0, 0.0, 8.0)];
+        force.clone().apply(&mut nodes, 1.0, &mut StepRng::new(0, 1));
+        // G · 2 · 8 / 10²
+        assert!((nodes[0].vx - 0.16).abs() < 1e-12 && (nodes[1].vx + 0.16).abs() < 1e-12);
+
+        // The engine divides by mass, so the light body moves four times as far
+        let options = PhysicsOptions { velocity_decay: 1.0, seed: Some(1), ..PhysicsOptions::default() };
+        let nodes = vec![node(0.0, 0.0, 2.0), node(10.0, 0.0, 8.0)];
+        let mut engine = PhysicsEngine::new(nodes, Vec::new(), Some(options)).unwrap();
+        engine.add_force("gravity", force);
+        engine.tick().unwrap();
+        let (light, heavy) = (engine.nodes[0].x, 10.0 - engine.nodes[1].x);
+        assert!(light > 0.0 && (light / heavy - 4.0).abs() < 1e-9, "{} {}", light, heavy);
+    }
+
+    #[test]
+    fn barnes_hut_stays_close_to_the_exact_field() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let nodes: Vec<Node> = (0..500)
+            .map(|_| node(rng.gen::<f64>() * 1000.0, rng.gen::<f64>() * 1000.0, rng.gen_range(1.0..10.0)))
+            .collect();
+        let (mut exact, mut approximate) = (nodes.clone(), nodes);
+        NewtonianGravityForce { theta: None, ..Default::default() }.apply(&mut exact, 1.0, &mut rng);
+        NewtonianGravityForce::default().apply(&mut approximate, 1.0, &mut rng);
+
+        let (mut error, mut magnitude) = (0.0, 0.0);
+        for (a, b) in exact.iter().zip(&approximate) {
+            error += (a.vx - b.vx).hypot(a.vy - b.vy);
+            magnitude += a.vx.hypot(a.vy);
+        }
+        assert!(error / magnitude < 0.02, "total {}", error / magnitude);
+    }
+}
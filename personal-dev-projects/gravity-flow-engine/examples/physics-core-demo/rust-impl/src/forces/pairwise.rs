@@ -0,0 +1,101 @@
+// Long-range interactions between every pair of nodes, summed exactly or
+// with the Barnes-Hut approximation
+
+use super::jiggle;
+use crate::quadtree::QuadTree;
+use rand::RngCore;
+
+// Pull between two values at the given squared distance, positive toward
+// each other. Must be symmetric in the two values.
+pub type Pull<'a> = dyn Fn(f64, f64, f64) -> f64 + 'a;
+
+// Exact O(n²) sum over all pairs, applying each pull to both ends
+pub fn exact(positions: &[(f64, f64)], values: &[f64], pull: &Pull, rng: &mut dyn RngCore) -> Vec<(f64, f64)> {
+    let mut field = vec![(0.0, 0.0); positions.len()];
+
+    for i in 0..positions.len() {
+        for j in (i + 1)..positions.len() {
+            let (mut dx, mut dy) = (positions[j].0 - positions[i].0, positions[j].1 - positions[i].1);
+            if dx == 0.0 && dy == 0.0 {
+                dx = jiggle(rng);
+                dy = jiggle(rng);
+            }
+            let distance_squared = dx * dx + dy * dy;
+            let distance = distance_squared.sqrt();
+            let force = pull(values[i], values[j], distance_squared);
+
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            field[i].0 += fx;
+            field[i].1 += fy;
+            field[j].0 -= fx;
+            field[j].1 -= fy;
+        }
+    }
+
+    field
+}
+
+// Pull on every node (with its own `values`) from the bodies of `sources`,
+// treating distant cells of `tree` as a single body at their centroid
+pub fn barnes_hut(
+    tree: &QuadTree,
+    positions: &[(f64, f64)],
+    values: &[f64],
+    sources: &[f64],
+    theta: f64,
+    pull: &Pull,
+    rng: &mut dyn RngCore,
+) -> Vec<(f64, f64)> {
+    let aggregates = tree.aggregate(sources);
+    let theta_squared = theta * theta;
+
+    (0..positions.len())
+        .map(|i| {
+            let (x_i, y_i) = positions[i];
+            let (mut force_x, mut force_y) = (0.0, 0.0);
+            if values[i] == 0.0 {
+                return (force_x, force_y);
+            }
+
+            // Accumulate the pull of a body of the given value at (x, y)
+            let mut accumulate = |x: f64, y: f64, value: f64| {
+                let (mut dx, mut dy) = (x - x_i, y - y_i);
+                if dx == 0.0 && dy == 0.0 {
+                    dx = jiggle(rng);
+                    dy = jiggle(rng);
+                }
+                let distance_squared = dx * dx + dy * dy;
+                let distance = distance_squared.sqrt();
+                let force = pull(values[i], value, distance_squared);
+                force_x += dx / distance * force;
+                force_y += dy / distance * force;
+            };
+
+            tree.visit(|index, cell| {
+                let aggregate = aggregates[index];
+                if aggregate.value == 0.0 {
+                    return false;
+                }
+
+                if cell.is_leaf() {
+                    for j in tree.bodies(cell).filter(|&j| j != i && sources[j] != 0.0) {
+                        accumulate(positions[j].0, positions[j].1, sources[j]);
+                    }
+                    return false;
+                }
+
+                // Treat distant cells as a single body at their centroid
+                let dx = aggregate.x - x_i;
+                let dy = aggregate.y - y_i;
+                if cell.size * cell.size < theta_squared * (dx * dx + dy * dy) {
+                    accumulate(aggregate.x, aggregate.y, aggregate.value);
+                    return false;
+                }
+
+                true
+            });
+
+            (force_x, force_y)
+        })
+        .collect()
+}
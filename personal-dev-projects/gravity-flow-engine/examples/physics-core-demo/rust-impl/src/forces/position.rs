@@ -0,0 +1,125 @@
+use super::Force;
+use crate::physics_engine::{Link, Node};
+use rand::RngCore;
+
+// Per-node parameter of a positioning force. Accessors are evaluated when the
+// force is initialized, as in d3, not on every tick.
+pub type NodeAccessor = Box<dyn Fn(&Node) -> f64>;
+
+// Accessor returning the same value for every node
+pub fn constant(value: f64) -> NodeAccessor {
+    Box::new(move |_| value)
+}
+
+fn evaluate(accessor: &NodeAccessor, nodes: &[Node]) -> Vec<f64> {
+    nodes.iter().map(accessor).collect()
+}
+
+// Pulls each node toward its target x, like d3.forceX
+pub struct PositionXForce {
+    pub x: NodeAccessor,
+    pub strength: NodeAccessor,
+    targets: Vec<f64>,
+    strengths: Vec<f64>,
+}
+
+impl PositionXForce {
+    // Same target for every node, with d3's default strength
+    pub fn new(x: f64) -> Self {
+        PositionXForce {
+            x: constant(x),
+            strength: constant(0.1),
+            targets: Vec::new(),
+            strengths: Vec::new(),
+        }
+    }
+}
+
+impl Force for PositionXForce {
+    fn initialize(&mut self, nodes: &[Node], _links: &[Link], _endpoints: &[(usize, usize)]) {
+        self.targets = evaluate(&self.x, nodes);
+        self.strengths = evaluate(&self.strength, nodes);
+    }
+
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, _rng: &mut dyn RngCore) {
+        for ((node, &x), &strength) in nodes.iter_mut().zip(&self.targets).zip(&self.strengths) {
+            node.vx += (x - node.x) * strength * alpha;
+        }
+    }
+}
+
+// Pulls each node toward its target y, like d3.forceY
+pub struct PositionYForce {
+    pub y: NodeAccessor,
+    pub strength: NodeAccessor,
+    targets: Vec<f64>,
+    strengths: Vec<f64>,
+}
+
+impl PositionYForce {
+    // Same target for every node, with d3's default strength
+    pub fn new(y: f64) -> Self {
+        PositionYForce {
+            y: constant(y),
+            strength: constant(0.1),
+            targets: Vec::new(),
+            strengths: Vec::new(),
+        }
+    }
+}
+
+impl Force for PositionYForce {
+    fn initialize(&mut self, nodes: &[Node], _links: &[Link], _endpoints: &[(usize, usize)]) {
+        self.targets = evaluate(&self.y, nodes);
+        self.strengths = evaluate(&self.strength, nodes);
+    }
+
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, _rng: &mut dyn RngCore) {
+        for ((node, &y), &strength) in nodes.iter_mut().zip(&self.targets).zip(&self.strengths) {
+            node.vy += (y - node.y) * strength * alpha;
+        }
+    }
+}
+
+// Pulls each node toward a circle of its target radius around (x, y), like
+// d3.forceRadial
+pub struct RadialForce {
+    pub radius: NodeAccessor,
+    pub strength: NodeAccessor,
+    pub x: f64,
+    pub y: f64,
+    radii: Vec<f64>,
+    strengths: Vec<f64>,
+}
+
+impl RadialForce {
+    // Same radius for every node, with d3's default strength
+    pub fn new(radius: f64, x: f64, y: f64) -> Self {
+        RadialForce {
+            radius: constant(radius),
+            strength: constant(0.1),
+            x,
+            y,
+            radii: Vec::new(),
+            strengths: Vec::new(),
+        }
+    }
+}
+
+impl Force for RadialForce {
+    fn initialize(&mut self, nodes: &[Node], _links: &[Link], _endpoints: &[(usize, usize)]) {
+        self.radii = evaluate(&self.radius, nodes);
+        self.strengths = evaluate(&self.strength, nodes);
+    }
+
+    fn apply(&mut self, nodes: &mut [Node], alpha: f64, _rng: &mut dyn RngCore) {
+        for ((node, &radius), &strength) in nodes.iter_mut().zip(&self.radii).zip(&self.strengths) {
+            let (dx, dy) = (node.x - self.x, node.y - self.y);
+            // A node exactly on the center has no direction to move in
+            let distance = (dx * dx + dy * dy).sqrt().max(1e-6);
+            let k = (radius - distance) * strength * alpha / distance;
+            node.vx += dx * k;
+            node.vy += dy * k;
+        }
+    }
+}
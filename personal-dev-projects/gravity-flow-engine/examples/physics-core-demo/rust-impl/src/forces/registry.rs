@@ -0,0 +1,276 @@
+use super::Force;
+use crate::error::PhysicsError;
+use crate::physics_engine::{Link, Node};
+use rand::RngCore;
+
+struct Entry {
+    name: String,
+    force: Box<dyn Force>,
+    enabled: bool,
+}
+
+// Named forces kept in the order they are applied on each tick
+#[derive(Default)]
+pub struct ForceRegistry {
+    entries: Vec<Entry>,
+}
+
+impl ForceRegistry {
+    pub fn new() -> Self {
+        ForceRegistry::default()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    // Force names in application order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+    
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+    
+    pub fn get(&self, name: &str) -> Option<&dyn Force> {
+        let index = self.position(name)?;
+        Some(self.entries[index].force.as_ref())
+    }
+    
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut (dyn Force + 'static)> {
+        let index = self.position(name)?;
+        Some(self.entries[index].force.as_mut())
+    }
+    
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.name == name)
+    }
+    
+    // Appends the force, or replaces an existing force of the same name in place
+    pub fn insert(&mut self, name: &str, force: Box<dyn Force>) -> Option<Box<dyn Force>> {
+        match self.position(name) {
+            Some(index) => Some(std::mem::replace(&mut self.entries[index].force, force)),
+            None => {
+                self.entries.push(Entry { name: name.to_string(), force, enabled: true });
+                None
+            }
+        }
+    }
+    
+    pub fn insert_before(&mut self, anchor: &str, name: &str, force: Box<dyn Force>) -> Result<(), PhysicsError> {
+        let index = self.insertion_point(anchor, name)?;
+        self.entries.insert(index, Entry { name: name.to_string(), force, enabled: true });
+        Ok(())
+    }
+    
+    pub fn insert_after(&mut self, anchor: &str, name: &str, force: Box<dyn Force>) -> Result<(), PhysicsError> {
+        let index = self.insertion_point(anchor, name)?;
+        self.entries.insert(index + 1, Entry { name: name.to_string(), force, enabled: true });
+        Ok(())
+    }
+    
+    fn insertion_point(&self, anchor: &str, name: &str) -> Result<usize, PhysicsError> {
+        if self.contains(name) {
+            return Err(PhysicsError::DuplicateForce(name.to_string()));
+        }
+        self.position(anchor)
+            .ok_or_else(|| PhysicsError::UnknownForce(anchor.to_string()))
+    }
+    
+    // Swaps in a new force at the same position, returning the old one
+    pub fn replace(&mut self, name: &str, force: Box<dyn Force>) -> Result<Box<dyn Force>, PhysicsError> {
+        let index = self.position(name)
+            .ok_or_else(|| PhysicsError::UnknownForce(name.to_string()))?;
+        Ok(std::mem::replace(&mut self.entries[index].force, force))
+    }
+    
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn Force>> {
+        let index = self.position(name)?;
+        Some(self.entries.remove(index).force)
+    }
+    
+    // Disabled forces keep their position but are skipped on each tick
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), PhysicsError> {
+        let index = self.position(name)
+            .ok_or_else(|| PhysicsError::UnknownForce(name.to_string()))?;
+        self.entries[index].enabled = enabled;
+        Ok(())
+    }
+    
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.position(name).is_some_and(|index| self.entries[index].enabled)
+    }
+    
+    pub fn initialize(&mut self, nodes: &[Node], links: &[Link], endpoints: &[(usize, usize)]) {
+        for entry in &mut self.entries {
+            entry.force.initialize(nodes, links, endpoints);
+        }
+    }
+    
+    // Applies every enabled force in order. The velocity change of a
+    // mass-scaled force is divided by each node's mass before the next force
+    // runs, so relaxation forces see the velocities nodes will really have.
+    pub fn apply(&mut self, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore) {
+        for entry in self.entries.iter_mut().filter(|entry| entry.enabled) {
+            if !entry.force.mass_scaled() {
+                entry.force.apply(nodes, alpha, rng);
+                continue;
+            }
+            
+            let velocities: Vec<(f64, f64)> = nodes.iter().map(|node| (node.vx, node.vy)).collect();
+            entry.force.apply(nodes, alpha, rng);
+            for (node, (vx, vy)) in nodes.iter_mut().zip(velocities) {
+                let inverse_mass = node.physical_properties.inverse_mass();
+                node.vx = vx + (node.vx - vx) * inverse_mass;
+                node.vy = vy + (node.vy - vy) * inverse_mass;
+            }
+        }
+    }
+    
+    pub fn adjust_positions(&mut self, nodes: &mut [Node]) {
+        for entry in self.entries.iter_mut().filter(|entry| entry.enabled) {
+            entry.force.adjust_positions(nodes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::PhysicalProperties;
+    use rand::rngs::mock::StepRng;
+    use std::collections::BTreeMap;
+    
+    // Adds a fixed velocity to every node
+    struct Push(f64);
+    
+    impl Force for Push {
+        fn apply(&mut self, nodes: &mut [Node], _alpha: f64, _rng: &mut dyn RngCore) {
+            for node in nodes {
+                node.vx += self.0;
+            }
+        }
+    }
+    
+    fn push(value: f64) -> Box<dyn Force> {
+        Box::new(Push(value))
+    }
+    
+    fn registry(names: &[&str]) -> ForceRegistry {
+        let mut registry = ForceRegistry::new();
+        for name in names {
+            registry.insert(name, push(1.0));
+        }
+        registry
+    }
+    
+    fn names(registry: &ForceRegistry) -> Vec<&str> {
+        registry.names().collect()
+    }
+    
+    fn node(mass: f64) -> Node {
+        Node {
+            id: "a".to_string(),
+            label: String::new(),
+            category: String::new(),
+            physical_properties: PhysicalProperties {
+                mass,
+                charge: 0.0,
+                friction: 0.0,
+                radius: 1.0,
+                fixed: false,
+                initial_position: None,
+            },
+            metadata: BTreeMap::new(),
+            x: 0.0,
+            y: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            fx: None,
+            fy: None,
+        }
+    }
+    
+    #[test]
+    fn insertions_keep_the_requested_order() {
+        let mut registry = registry(&["link", "charge"]);
+        registry.insert_before("link", "center", push(1.0)).unwrap();
+        assert_eq!(names(&registry), ["center", "link", "charge"]);
+        registry.insert_after("link", "collide", push(1.0)).unwrap();
+        assert_eq!(names(&registry), ["center", "link", "collide", "charge"]);
+        registry.insert_after("charge", "group", push(1.0)).unwrap();
+        assert_eq!(names(&registry), ["center", "link", "collide", "charge", "group"]);
+        
+        assert_eq!(
+            registry.insert_before("missing", "x", push(1.0)).err(),
+            Some(PhysicsError::UnknownForce("missing".to_string()))
+        );
+        assert_eq!(
+            registry.insert_after("link", "charge", push(1.0)).err(),
+            Some(PhysicsError::DuplicateForce("charge".to_string()))
+        );
+        assert_eq!(registry.len(), 5);
+    }
+    
+    #[test]
+    fn insert_and_replace_keep_the_position() {
+        let mut registry = registry(&["link", "charge", "center"]);
+        assert!(registry.insert("charge", push(2.0)).is_some());
+        assert_eq!(names(&registry), ["link", "charge", "center"]);
+        
+        assert!(registry.replace("link", push(3.0)).is_ok());
+        assert_eq!(names(&registry), ["link", "charge", "center"]);
+        assert_eq!(
+            registry.replace("missing", push(1.0)).err(),
+            Some(PhysicsError::UnknownForce("missing".to_string()))
+        );
+        
+        let mut nodes = [node(1.0)];
+        registry.apply(&mut nodes, 1.0, &mut StepRng::new(0, 1));
+        assert_eq!(nodes[0].vx, 6.0);
+    }
+    
+    #[test]
+    fn remove_drops_only_the_named_force() {
+        let mut registry = registry(&["link", "charge", "center"]);
+        assert!(registry.remove("charge").is_some());
+        assert_eq!(names(&registry), ["link", "center"]);
+        assert!(registry.remove("charge").is_none());
+        assert!(!registry.contains("charge"));
+    }
+    
+    #[test]
+    fn disabled_forces_keep_their_place_and_are_skipped() {
+        let mut registry = ForceRegistry::new();
+        registry.insert("small", push(1.0));
+        registry.insert("large", push(10.0));
+        registry.set_enabled("large", false).unwrap();
+        assert!(!registry.is_enabled("large") && registry.is_enabled("small"));
+        assert_eq!(names(&registry), ["small", "large"]);
+        assert_eq!(
+            registry.set_enabled("missing", false).err(),
+            Some(PhysicsError::UnknownForce("missing".to_string()))
+        );
+        
+        let mut nodes = [node(1.0)];
+        registry.apply(&mut nodes, 1.0, &mut StepRng::new(0, 1));
+        assert_eq!(nodes[0].vx, 1.0);
+        
+        registry.set_enabled("large", true).unwrap();
+        registry.apply(&mut nodes, 1.0, &mut StepRng::new(0, 1));
+        assert_eq!(nodes[0].vx, 12.0);
+    }
+    
+    #[test]
+    fn apply_divides_mass_scaled_forces_by_mass() {
+        let mut registry = registry(&["push"]);
+        let mut nodes = [node(4.0)];
+        registry.apply(&mut nodes, 1.0, &mut StepRng::new(0, 1));
+        assert_eq!(nodes[0].vx, 0.25);
+    }
+}
@@ -0,0 +1,377 @@
+// Shared by the attribute-based formats (NetworkX, GraphML, GEXF, DOT):
+// typed attribute values, the mapping of attribute names onto Node / Link,
+// and XML escaping for the writers
+
+use super::ImportDefaults;
+use crate::physics_engine::{Link, Node, Position, RelationshipType, SpringModel};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// Name of the attribute each engine field is read from and written to.
+// The defaults are the NetworkX attribute names; set e.g. `stiffness` to
+// "weight" to lay out a Gephi graph with its edge weights as spring
+// stiffness. Attributes that map to no field are kept in `metadata`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeMapping {
+    pub label: String,
+    pub category: String,
+    pub mass: String,
+    pub charge: String,
+    pub friction: String,
+    pub radius: String,
+    pub fixed: String,
+    pub x: String,
+    pub y: String,
+    pub relationship_type: String,
+    pub stiffness: String,
+    pub length: String,
+    pub elasticity: String,
+    pub spring_model: String,
+    pub bidirectional: String,
+}
+
+impl Default for AttributeMapping {
+    fn default() -> Self {
+        AttributeMapping {
+            label: "label".to_string(),
+            category: "category".to_string(),
+            mass: "mass".to_string(),
+            charge: "charge".to_string(),
+            friction: "friction".to_string(),
+            radius: "radius".to_string(),
+            fixed: "fixed".to_string(),
+            x: "x".to_string(),
+            y: "y".to_string(),
+            relationship_type: "type".to_string(),
+            stiffness: "stiffness".to_string(),
+            length: "length".to_string(),
+            elasticity: "elasticity".to_string(),
+            spring_model: "spring_model".to_string(),
+            bidirectional: "bidirectional".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum NodeField {
+    Label,
+    Category,
+    Mass,
+    Charge,
+    Friction,
+    Radius,
+    Fixed,
+    X,
+    Y,
+}
+
+#[derive(Clone, Copy)]
+enum LinkField {
+    RelationshipType,
+    Stiffness,
+    Length,
+    Elasticity,
+    SpringModel,
+    Bidirectional,
+}
+
+// Attribute name and value, in document order
+pub(crate) type Attributes = Vec<(String, Value)>;
+
+impl AttributeMapping {
+    fn node_field(&self, name: &str) -> Option<NodeField> {
+        [
+            (&self.label, NodeField::Label),
+            (&self.category, NodeField::Category),
+            (&self.mass, NodeField::Mass),
+            (&self.charge, NodeField::Charge),
+            (&self.friction, NodeField::Friction),
+            (&self.radius, NodeField::Radius),
+            (&self.fixed, NodeField::Fixed),
+            (&self.x, NodeField::X),
+            (&self.y, NodeField::Y),
+        ]
+        .into_iter()
+        .find(|(field, _)| field.as_str() == name)
+        .map(|(_, field)| field)
+    }
+
+    fn link_field(&self, name: &str) -> Option<LinkField> {
+        [
+            (&self.relationship_type, LinkField::RelationshipType),
+            (&self.stiffness, LinkField::Stiffness),
+            (&self.length, LinkField::Length),
+            (&self.elasticity, LinkField::Elasticity),
+            (&self.spring_model, LinkField::SpringModel),
+            (&self.bidirectional, LinkField::Bidirectional),
+        ]
+        .into_iter()
+        .find(|(field, _)| field.as_str() == name)
+        .map(|(_, field)| field)
+    }
+
+    // Node with the import defaults, overridden by its attributes. Later
+    // attributes win over earlier ones of the same name; null ones leave a
+    // mapped field as it is.
+    pub(crate) fn node(&self, id: String, attributes: Attributes, defaults: &ImportDefaults) -> Result<Node, String> {
+        let mut properties = defaults.node.clone();
+        let mut label = None;
+        let mut category = String::new();
+        let (mut x, mut y) = (None, None);
+        let mut metadata = BTreeMap::new();
+
+        for (name, value) in attributes {
+            match self.node_field(&name) {
+                Some(_) if value.is_null() => {}
+                Some(NodeField::Label) => label = Some(text(&value)),
+                Some(NodeField::Category) => category = text(&value),
+                Some(NodeField::Mass) => properties.mass = number(&name, &value)?,
+                Some(NodeField::Charge) => properties.charge = number(&name, &value)?,
+                Some(NodeField::Friction) => properties.friction = number(&name, &value)?,
+                Some(NodeField::Radius) => properties.radius = number(&name, &value)?,
+                Some(NodeField::Fixed) => properties.fixed = boolean(&name, &value)?,
+                Some(NodeField::X) => x = Some(number(&name, &value)?),
+                Some(NodeField::Y) => y = Some(number(&name, &value)?),
+                None => {
+                    metadata.insert(name, value);
+                }
+            }
+        }
+
+        // A position from an earlier layout is where the node starts
+        if let (Some(x), Some(y)) = (x, y) {
+            properties.initial_position = Some(Position { x, y });
+        }
+
+        Ok(Node {
+            label: label.unwrap_or_else(|| id.clone()),
+            id,
+            category,
+            physical_properties: properties,
+            metadata,
+            x: x.unwrap_or(0.0),
+            y: y.unwrap_or(0.0),
+            vx: 0.0,
+            vy: 0.0,
+            fx: None,
+            fy: None,
+        })
+    }
+
+    // Link with the import defaults, overridden by its attributes.
+    // `bidirectional` comes from the file's edge direction and gives way to
+    // a mapped attribute. A relationship type the engine doesn't know is
+    // kept in `metadata` as it was, and written back from there.
+    pub(crate) fn link(
+        &self,
+        source: String,
+        target: String,
+        bidirectional: bool,
+        attributes: Attributes,
+        defaults: &ImportDefaults,
+    ) -> Result<Link, String> {
+        let mut properties = defaults.link.clone();
+        properties.bidirectional = bidirectional;
+        let mut relationship_type = RelationshipType::default();
+        let mut metadata = BTreeMap::new();
+
+        for (name, value) in attributes {
+            match self.link_field(&name) {
+                Some(_) if value.is_null() => {}
+                Some(LinkField::RelationshipType) => match RelationshipType::deserialize(&value) {
+                    Ok(RelationshipType::Other) | Err(_) => {
+                        relationship_type = RelationshipType::Other;
+                        metadata.insert(name, value);
+                    }
+                    Ok(known) => {
+                        relationship_type = known;
+                        metadata.remove(&name);
+                    }
+                },
+                Some(LinkField::Stiffness) => properties.stiffness = number(&name, &value)?,
+                Some(LinkField::Length) => properties.length = number(&name, &value)?,
+                Some(LinkField::Elasticity) => properties.elasticity = number(&name, &value)?,
+                Some(LinkField::SpringModel) => {
+                    properties.spring_model = SpringModel::deserialize(Value::from(text(&value)))
+                        .map_err(|_| format!("unknown \"{}\"", name))?;
+                }
+                Some(LinkField::Bidirectional) => properties.bidirectional = boolean(&name, &value)?,
+                None => {
+                    metadata.insert(name, value);
+                }
+            }
+        }
+
+        Ok(Link {
+            source,
+            target,
+            relationship_type,
+            physical_properties: properties,
+            metadata,
+        })
+    }
+
+    // Attributes written for a node: its metadata, then the mapped fields
+    // with the current position as x/y
+    pub(crate) fn node_attributes(&self, node: &Node) -> Attributes {
+        let properties = &node.physical_properties;
+        let mapped = [
+            (&self.label, Value::from(node.label.as_str())),
+            (&self.category, Value::from(node.category.as_str())),
+            (&self.mass, Value::from(properties.mass)),
+            (&self.charge, Value::from(properties.charge)),
+            (&self.friction, Value::from(properties.friction)),
+            (&self.radius, Value::from(properties.radius)),
+            (&self.fixed, Value::from(properties.fixed)),
+            (&self.x, Value::from(node.x)),
+            (&self.y, Value::from(node.y)),
+        ];
+        with_metadata(&node.metadata, mapped.map(|(name, value)| (name.clone(), value)))
+    }
+
+    pub(crate) fn link_attributes(&self, link: &Link) -> Attributes {
+        let properties = &link.physical_properties;
+        let relationship_type = match link.metadata.get(&self.relationship_type) {
+            Some(original) if link.relationship_type == RelationshipType::Other => original.clone(),
+            _ => serde_json::to_value(link.relationship_type).unwrap_or_default(),
+        };
+        let mapped = [
+            (&self.relationship_type, relationship_type),
+            (&self.stiffness, Value::from(properties.stiffness)),
+            (&self.length, Value::from(properties.length)),
+            (&self.elasticity, Value::from(properties.elasticity)),
+            (&self.spring_model, serde_json::to_value(properties.spring_model).unwrap_or_default()),
+            (&self.bidirectional, Value::from(properties.bidirectional)),
+        ];
+        with_metadata(&link.metadata, mapped.map(|(name, value)| (name.clone(), value)))
+    }
+}
+
+// Metadata entries not shadowed by a mapped field, followed by the fields
+fn with_metadata<const N: usize>(metadata: &BTreeMap<String, Value>, mapped: [(String, Value); N]) -> Attributes {
+    let mut attributes: Attributes = metadata
+        .iter()
+        .filter(|(key, _)| !mapped.iter().any(|(name, _)| name == *key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    attributes.extend(mapped);
+    attributes
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// Numbers may also come from attributes declared as strings
+pub(crate) fn number(name: &str, value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("\"{}\" must be a number", name))
+}
+
+fn boolean(name: &str, value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Bool(flag) => Some(*flag),
+        Value::String(text) => parse_boolean(text),
+        _ => None,
+    }
+    .ok_or_else(|| format!("\"{}\" must be a boolean", name))
+}
+
+fn parse_boolean(text: &str) -> Option<bool> {
+    match text.trim() {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+// Declared type of attributes holding arrays or objects, which neither
+// GraphML nor GEXF can type. They are declared as strings holding JSON text,
+// with this extra XML attribute on the declaration so they are parsed back.
+pub(crate) const JSON: &str = "json";
+pub(crate) const JSON_MARKER: &str = "gravity.type";
+
+// Type written in a declaration, followed by the marker for JSON attributes
+pub(crate) fn xml_type(kind: &'static str) -> (&'static str, &'static str) {
+    match kind {
+        JSON => ("string", " gravity.type=\"json\""),
+        kind => (kind, ""),
+    }
+}
+
+// Value of an attribute of a declared type, as GraphML ("int", "double", ...)
+// and GEXF ("integer", "double", ...) spell them, or JSON. Other types, such
+// as GEXF lists, are read as strings.
+pub(crate) fn typed_value(kind: &str, text: &str) -> Result<Value, String> {
+    let invalid = || format!("\"{}\" is not a valid {}", text, kind);
+    match kind {
+        JSON => serde_json::from_str(text).map_err(|_| invalid()),
+        "int" | "integer" | "long" => text.trim().parse::<i64>().map(Value::from).map_err(|_| invalid()),
+        "float" | "double" => text.trim().parse::<f64>().map(Value::from).map_err(|_| invalid()),
+        "boolean" => parse_boolean(text).map(Value::from).ok_or_else(invalid),
+        _ => Ok(Value::from(text)),
+    }
+}
+
+// Name and declared type of every attribute of the rows, in order of first
+// use. Arrays and objects are declared JSON. Mixed long and double values are
+// declared double, other mixes string. Null values, which the writers leave
+// out, don't count, and attributes that are always null aren't declared.
+pub(crate) fn declare(rows: &[Attributes]) -> Vec<(String, &'static str)> {
+    let mut keys: Vec<(String, Option<&'static str>)> = Vec::new();
+    for (name, value) in rows.iter().flatten() {
+        let kind = match value {
+            Value::Null => None,
+            Value::Bool(_) => Some("boolean"),
+            Value::Number(number) if number.is_f64() => Some("double"),
+            Value::Number(_) => Some("long"),
+            Value::String(_) => Some("string"),
+            Value::Array(_) | Value::Object(_) => Some(JSON),
+        };
+        match keys.iter_mut().find(|(key, _)| key == name) {
+            Some((_, declared)) => {
+                *declared = match (*declared, kind) {
+                    (a, None) | (None, a) => a,
+                    (a, b) if a == b => a,
+                    (Some("long"), Some("double")) | (Some("double"), Some("long")) => Some("double"),
+                    _ => Some("string"),
+                }
+            }
+            None => keys.push((name.clone(), kind)),
+        }
+    }
+    keys.into_iter().filter_map(|(name, kind)| Some((name, kind?))).collect()
+}
+
+// Text written for a value; arrays and objects are written as JSON
+pub(crate) fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        other => text(other),
+    }
+}
+
+// Escapes text for use in XML content and double-quoted attributes
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
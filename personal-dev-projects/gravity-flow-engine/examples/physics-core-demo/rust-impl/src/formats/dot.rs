@@ -0,0 +1,633 @@
+// Graphviz DOT:
+//
+//   digraph flow {
+//     node [mass=5];
+//     subgraph cluster_process {
+//       feedback [label="Feedback Loops"];
+//       homeostasis;
+//     }
+//     feedback -> homeostasis [type=causal];
+//   }
+//
+// Nodes take the category of the innermost named subgraph they first
+// appear in, with a `cluster_` prefix removed. Attributes are mapped onto
+// Node / Link through an AttributeMapping; as DOT values are untyped,
+// unmapped ones are kept in `metadata` as strings. `pos` ("x,y", with or
+// without neato's `!`) is the initial position, and Graphviz's y axis
+// points up, so y is negated on the way in and out. `dir=both` and
+// `dir=none` make an edge of a digraph two-way, `dir=back` reverses it.
+// Ports are ignored.
+
+use super::attributes::{value_text, AttributeMapping, Attributes};
+use super::{read_file, write_file, ImportDefaults};
+use crate::error::PhysicsError;
+use crate::graph::Graph;
+use crate::physics_engine::Link;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+fn error(line: usize, message: impl Into<String>) -> PhysicsError {
+    PhysicsError::Parse {
+        format: "DOT",
+        message: format!("line {}: {}", line, message.into()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    // Identifier, numeral, quoted or HTML string; `quoted` IDs are never keywords
+    Id { text: String, quoted: bool },
+    // `->` (true) or `--` (false)
+    EdgeOp(bool),
+    Symbol(char),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, PhysicsError> {
+    let mut tokens: Vec<(Token, usize)> = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        let start = line;
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            // Preprocessor output lines
+            '#' if line_start => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    let c = chars.next().ok_or_else(|| error(start, "unterminated comment"))?;
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    line += usize::from(c == '\n');
+                    previous = c;
+                }
+            }
+            '-' if matches!(chars.peek(), Some('>' | '-')) => {
+                tokens.push((Token::EdgeOp(chars.next() == Some('>')), start));
+            }
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' | '+' => tokens.push((Token::Symbol(c), start)),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next().ok_or_else(|| error(start, "unterminated string"))? {
+                        '"' => break,
+                        '\\' => match chars.next().ok_or_else(|| error(start, "unterminated string"))? {
+                            c @ ('"' | '\\') => text.push(c),
+                            // Line continuation
+                            '\n' => line += 1,
+                            // Graphviz escapes such as `\n` and `\l` are kept as written
+                            c => {
+                                text.push('\\');
+                                text.push(c);
+                            }
+                        },
+                        c => {
+                            line += usize::from(c == '\n');
+                            text.push(c);
+                        }
+                    }
+                }
+                // "a" + "b" is one string
+                if let [.., (Token::Id { text: first, quoted: true }, _), (Token::Symbol('+'), _)] = &tokens[..] {
+                    text = format!("{}{}", first, text);
+                    tokens.truncate(tokens.len() - 2);
+                }
+                tokens.push((Token::Id { text, quoted: true }, start));
+            }
+            // HTML string, kept with its markup
+            '<' => {
+                let mut text = String::new();
+                let mut depth = 1;
+                loop {
+                    let c = chars.next().ok_or_else(|| error(start, "unterminated HTML string"))?;
+                    match c {
+                        '<' => depth += 1,
+                        '>' if depth == 1 => break,
+                        '>' => depth -= 1,
+                        '\n' => line += 1,
+                        _ => {}
+                    }
+                    text.push(c);
+                }
+                tokens.push((Token::Id { text, quoted: true }, start));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut text = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    text.push(c);
+                }
+                tokens.push((Token::Id { text, quoted: false }, start));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut text = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    text.push(c);
+                }
+                tokens.push((Token::Id { text, quoted: false }, start));
+            }
+            c => return Err(error(start, format!("unexpected '{}'", c))),
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+const KEYWORDS: [&str; 6] = ["graph", "digraph", "node", "edge", "subgraph", "strict"];
+
+// A node as declared so far
+struct DotNode {
+    id: String,
+    line: usize,
+    category: Option<String>,
+    attributes: Attributes,
+}
+
+struct DotEdge {
+    source: String,
+    target: String,
+    line: usize,
+    attributes: Attributes,
+}
+
+// Attribute defaults and category of a graph or subgraph body
+#[derive(Clone, Default)]
+struct Scope {
+    node: Attributes,
+    edge: Attributes,
+    category: Option<String>,
+    root: bool,
+}
+
+#[derive(Default)]
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    directed: bool,
+    nodes: Vec<DotNode>,
+    index: HashMap<String, usize>,
+    edges: Vec<DotEdge>,
+    graph_attributes: Attributes,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    // Line of the next token, or of the last one at the end of the input
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).map(|(token, _)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    fn at_symbol(&self, symbol: char) -> bool {
+        self.peek() == Some(&Token::Symbol(symbol))
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id { text, quoted: false }) if text.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), PhysicsError> {
+        let line = self.line();
+        match self.next() {
+            Some(Token::Symbol(c)) if c == symbol => Ok(()),
+            _ => Err(error(line, format!("expected '{}'", symbol))),
+        }
+    }
+
+    fn id(&mut self) -> Result<String, PhysicsError> {
+        let line = self.line();
+        match self.next() {
+            Some(Token::Id { text, .. }) => Ok(text),
+            _ => Err(error(line, "expected an ID")),
+        }
+    }
+
+    fn parse(&mut self) -> Result<(), PhysicsError> {
+        if self.at_keyword("strict") {
+            self.next();
+        }
+        self.directed = if self.at_keyword("digraph") {
+            true
+        } else if self.at_keyword("graph") {
+            false
+        } else {
+            return Err(error(self.line(), "expected 'graph' or 'digraph'"));
+        };
+        self.next();
+        if matches!(self.peek(), Some(Token::Id { .. })) {
+            self.next();
+        }
+        self.expect('{')?;
+        self.statements(&mut Scope { root: true, ..Default::default() })?;
+        if self.position < self.tokens.len() {
+            return Err(error(self.line(), "unexpected content after the graph"));
+        }
+        Ok(())
+    }
+
+    // Statements up to and including the closing '}'; returns the ids of
+    // the nodes they mention
+    fn statements(&mut self, scope: &mut Scope) -> Result<Vec<String>, PhysicsError> {
+        let mut ids = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(error(self.line(), "expected '}'")),
+                Some(Token::Symbol('}')) => {
+                    self.next();
+                    return Ok(ids);
+                }
+                Some(Token::Symbol(';')) => {
+                    self.next();
+                }
+                _ => self.statement(scope, &mut ids)?,
+            }
+        }
+    }
+
+    fn statement(&mut self, scope: &mut Scope, ids: &mut Vec<String>) -> Result<(), PhysicsError> {
+        // Attribute defaults
+        for keyword in ["graph", "node", "edge"] {
+            if self.at_keyword(keyword) {
+                self.next();
+                let attributes = self.attributes()?;
+                match keyword {
+                    "graph" if scope.root => self.graph_attributes.extend(attributes),
+                    "node" => scope.node.extend(attributes),
+                    "edge" => scope.edge.extend(attributes),
+                    _ => {}
+                }
+                return Ok(());
+            }
+        }
+
+        if self.at_symbol('{') || self.at_keyword("subgraph") {
+            let group = self.subgraph(scope)?;
+            ids.extend(group.iter().cloned());
+            return self.edges(group, scope, ids);
+        }
+
+        let id = self.id()?;
+        // `name = value` sets a graph attribute; those of subgraphs are ignored
+        if self.at_symbol('=') {
+            self.next();
+            let value = self.id()?;
+            if scope.root {
+                self.graph_attributes.push((id, Value::from(value)));
+            }
+            return Ok(());
+        }
+        self.port()?;
+        self.touch(&id, scope);
+        ids.push(id.clone());
+        if matches!(self.peek(), Some(Token::EdgeOp(_))) {
+            return self.edges(vec![id], scope, ids);
+        }
+        let attributes = self.attributes()?;
+        self.nodes[self.index[&id]].attributes.extend(attributes);
+        Ok(())
+    }
+
+    fn subgraph(&mut self, scope: &Scope) -> Result<Vec<String>, PhysicsError> {
+        let mut inner = Scope { root: false, ..scope.clone() };
+        if self.at_keyword("subgraph") {
+            self.next();
+            if matches!(self.peek(), Some(Token::Id { .. })) {
+                let name = self.id()?;
+                let name = name.strip_prefix("cluster_").or(name.strip_prefix("cluster")).unwrap_or(&name);
+                inner.category = Some(name.to_string());
+            }
+        }
+        self.expect('{')?;
+        self.statements(&mut inner)
+    }
+
+    // Skips `:port` and `:port:compass`
+    fn port(&mut self) -> Result<(), PhysicsError> {
+        while self.at_symbol(':') {
+            self.next();
+            self.id()?;
+        }
+        Ok(())
+    }
+
+    // Rest of an edge statement whose first endpoints are `first`
+    fn edges(&mut self, first: Vec<String>, scope: &mut Scope, ids: &mut Vec<String>) -> Result<(), PhysicsError> {
+        let line = self.line();
+        let mut groups = vec![first];
+        while let Some(&Token::EdgeOp(arrow)) = self.peek() {
+            if arrow != self.directed {
+                let message = if self.directed { "'--' in a digraph" } else { "'->' in an undirected graph" };
+                return Err(error(self.line(), message));
+            }
+            self.next();
+            let group = if self.at_symbol('{') || self.at_keyword("subgraph") {
+                self.subgraph(scope)?
+            } else {
+                let id = self.id()?;
+                self.port()?;
+                self.touch(&id, scope);
+                vec![id]
+            };
+            ids.extend(group.iter().cloned());
+            groups.push(group);
+        }
+
+        let attributes = self.attributes()?;
+        for pair in groups.windows(2) {
+            for source in &pair[0] {
+                for target in &pair[1] {
+                    let mut edge_attributes = scope.edge.clone();
+                    edge_attributes.extend(attributes.iter().cloned());
+                    self.edges.push(DotEdge {
+                        source: source.clone(),
+                        target: target.clone(),
+                        line,
+                        attributes: edge_attributes,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Any number of `[name=value, ...]` lists
+    fn attributes(&mut self) -> Result<Attributes, PhysicsError> {
+        let mut attributes = Attributes::new();
+        while self.at_symbol('[') {
+            self.next();
+            loop {
+                match self.peek() {
+                    Some(Token::Symbol(']')) => {
+                        self.next();
+                        break;
+                    }
+                    Some(Token::Symbol(',' | ';')) => {
+                        self.next();
+                    }
+                    _ => {
+                        let name = self.id()?;
+                        self.expect('=')?;
+                        attributes.push((name, Value::from(self.id()?)));
+                    }
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    // Declares a node on first mention; a node first mentioned outside
+    // any named subgraph takes the category of the next one it appears in
+    fn touch(&mut self, id: &str, scope: &Scope) {
+        match self.index.get(id) {
+            Some(&i) => {
+                let node = &mut self.nodes[i];
+                if node.category.is_none() {
+                    node.category = scope.category.clone();
+                }
+            }
+            None => {
+                self.index.insert(id.to_string(), self.nodes.len());
+                self.nodes.push(DotNode {
+                    id: id.to_string(),
+                    line: self.line(),
+                    category: scope.category.clone(),
+                    attributes: scope.node.clone(),
+                });
+            }
+        }
+    }
+}
+
+// Parses and validates a DOT graph. Edges of a digraph are one-way unless
+// `dir` says otherwise.
+pub fn from_str(input: &str, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        ..Default::default()
+    };
+    parser.parse()?;
+
+    let nodes = parser
+        .nodes
+        .into_iter()
+        .map(|node| {
+            let mut attributes = Attributes::new();
+            if let Some(category) = node.category {
+                attributes.push((mapping.category.clone(), Value::from(category)));
+            }
+            for (name, value) in node.attributes {
+                if name != "pos" {
+                    attributes.push((name, value));
+                    continue;
+                }
+                let (x, y) = position(value.as_str().unwrap_or_default())
+                    .ok_or_else(|| error(node.line, format!("node \"{}\": \"pos\" must be \"x,y\"", node.id)))?;
+                attributes.push((mapping.x.clone(), Value::from(x)));
+                attributes.push((mapping.y.clone(), Value::from(-y)));
+            }
+            mapping
+                .node(node.id.clone(), attributes, defaults)
+                .map_err(|message| error(node.line, format!("node \"{}\": {}", node.id, message)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let directed = parser.directed;
+    let links = parser
+        .edges
+        .into_iter()
+        .map(|edge| read_edge(edge, directed, mapping, defaults))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let metadata = parser.graph_attributes.into_iter().collect();
+    let graph = Graph { nodes, links, metadata };
+    graph.validate()?;
+    Ok(graph)
+}
+
+fn read_edge(edge: DotEdge, directed: bool, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Link, PhysicsError> {
+    let (mut source, mut target) = (edge.source, edge.target);
+    let mut one_way = directed;
+    let mut attributes = Attributes::new();
+    for (name, value) in edge.attributes {
+        if name != "dir" {
+            attributes.push((name, value));
+            continue;
+        }
+        match value.as_str().unwrap_or_default() {
+            "forward" => {}
+            "back" => std::mem::swap(&mut source, &mut target),
+            "both" | "none" => one_way = false,
+            other => return Err(error(edge.line, format!("unknown dir \"{}\"", other))),
+        }
+    }
+    mapping
+        .link(source, target, !one_way || !directed, attributes, defaults)
+        .map_err(|message| error(edge.line, message))
+}
+
+// "x,y", "x,y!" or "x,y,z"
+fn position(text: &str) -> Option<(f64, f64)> {
+    let mut coordinates = text.trim().trim_end_matches('!').split(',');
+    let x = coordinates.next()?.trim().parse().ok()?;
+    let y = coordinates.next()?.trim().parse().ok()?;
+    Some((x, y))
+}
+
+// DOT for a graph, for `neato -n`: every node is pinned at its current
+// position with `pos="x,y!"`, and each category becomes a
+// `cluster_<category>` subgraph. The graph is a digraph if any of its links
+// is one-way; its two-way links get `dir=none`.
+pub fn to_string(graph: &Graph, mapping: &AttributeMapping) -> String {
+    let directed = graph.links.iter().any(|link| !link.physical_properties.bidirectional);
+    let (keyword, edge_op) = if directed { ("digraph", "->") } else { ("graph", "--") };
+
+    let mut out = format!("{} {{\n", keyword);
+    for (name, value) in &graph.metadata {
+        let _ = writeln!(out, "  {}={};", quote(name), quote(&value_text(value)));
+    }
+
+    // Categories in order of first appearance; uncategorized nodes stay at the top level
+    let mut categories: Vec<&str> = Vec::new();
+    for node in &graph.nodes {
+        if !categories.contains(&node.category.as_str()) {
+            categories.push(&node.category);
+        }
+    }
+    for category in categories {
+        let indent = if category.is_empty() { "  " } else { "    " };
+        if !category.is_empty() {
+            let _ = writeln!(out, "  subgraph {} {{", quote(&format!("cluster_{}", category)));
+            let _ = writeln!(out, "    label={};", quote(category));
+        }
+        for node in graph.nodes.iter().filter(|node| node.category == category) {
+            let mut attributes = mapping.node_attributes(node);
+            attributes.retain(|(name, _)| *name != mapping.category && *name != mapping.x && *name != mapping.y);
+            attributes.push(("pos".to_string(), Value::from(format!("{},{}!", node.x, 0.0 - node.y))));
+            let _ = writeln!(out, "{}{} [{}];", indent, quote(&node.id), attribute_list(&attributes));
+        }
+        if !category.is_empty() {
+            out.push_str("  }\n");
+        }
+    }
+
+    for link in &graph.links {
+        let mut attributes = mapping.link_attributes(link);
+        if directed && link.physical_properties.bidirectional {
+            attributes.push(("dir".to_string(), Value::from("none")));
+        }
+        let _ = writeln!(
+            out,
+            "  {} {} {} [{}];",
+            quote(&link.source),
+            edge_op,
+            quote(&link.target),
+            attribute_list(&attributes)
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn attribute_list(attributes: &Attributes) -> String {
+    attributes
+        .iter()
+        .map(|(name, value)| format!("{}={}", quote(name), quote(&value_text(value))))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// An ID as written: bare if it's an identifier or a numeral, quoted otherwise.
+// A backslash is escaped where the lexer would otherwise read it as part of
+// an escape (before `\\`, `"` or a line break, or at the end), so Graphviz
+// escapes like `\n` are written as they were read. Line breaks are written
+// as they are.
+fn quote(text: &str) -> String {
+    let mut chars = text.chars();
+    let identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(text));
+    let numeral = text.parse::<f64>().is_ok() && text.chars().all(|c| c.is_ascii_digit() || c == '.' || c == '-');
+    if identifier || numeral {
+        text.to_string()
+    } else {
+        let mut quoted = String::with_capacity(text.len() + 2);
+        quoted.push('"');
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' if matches!(chars.peek(), None | Some('\\' | '"' | '\n')) => quoted.push_str("\\\\"),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+}
+
+pub fn read(path: impl AsRef<Path>, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    from_str(&read_file(path.as_ref())?, mapping, defaults)
+}
+
+pub fn write(path: impl AsRef<Path>, graph: &Graph, mapping: &AttributeMapping) -> Result<(), PhysicsError> {
+    write_file(path.as_ref(), &to_string(graph, mapping))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::networkx;
+
+    #[test]
+    fn round_trip_keeps_backslashes_and_quotes() {
+        let labels = ["ends with \\", "say \\\"hi\\\"", "left\\l", "two\nlines", "\"quoted\"", "a\\\\b", "line\\\nbreak"];
+        let nodes: Vec<_> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| serde_json::json!({ "id": format!("{}\\", i), "label": label, "note": label }))
+            .collect();
+        let mapping = AttributeMapping::default();
+        let defaults = ImportDefaults::default();
+        let graph = networkx::from_value(&serde_json::json!({ "nodes": nodes, "links": [] }), &mapping, &defaults).unwrap();
+
+        let text = to_string(&graph, &mapping);
+        let read = from_str(&text, &mapping, &defaults).unwrap();
+        for (node, label) in read.nodes.iter().zip(labels) {
+            assert_eq!(node.label, label);
+            assert_eq!(node.metadata["note"], label);
+        }
+        assert_eq!(read.nodes[0].id, "0\\");
+        assert_eq!(to_string(&read, &mapping), text);
+    }
+
+    #[test]
+    fn graphviz_escapes_are_written_as_read() {
+        let input = "digraph { a [label=\"first\\nsecond\\l\"]; }";
+        let mapping = AttributeMapping::default();
+        let graph = from_str(input, &mapping, &ImportDefaults::default()).unwrap();
+        assert_eq!(graph.nodes[0].label, "first\\nsecond\\l");
+        assert!(to_string(&graph, &mapping).contains("label=\"first\\nsecond\\l\""));
+    }
+}
@@ -0,0 +1,380 @@
+// GEXF, Gephi's native format (1.2 and 1.3):
+//
+//   <gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">
+//     <graph defaultedgetype="undirected">
+//       <attributes class="node">
+//         <attribute id="0" title="mass" type="double"/>
+//       </attributes>
+//       <nodes>
+//         <node id="feedback-loops" label="Feedback Loops">
+//           <attvalues><attvalue for="0" value="5"/></attvalues>
+//           <viz:position x="12.5" y="-40" z="0"/>
+//         </node>
+//       </nodes>
+//       <edges><edge id="0" source="feedback-loops" target="homeostasis"/></edges>
+//     </graph>
+//   </gexf>
+//
+// Node labels, `viz:position` and `viz:size` feed the mapped label, x/y and
+// radius unless an attribute of the same name says otherwise; edge labels
+// and weights are read as attributes "label" and "weight". Gephi's y axis
+// points up, so y is negated on the way in and out. Lists and objects are
+// written as JSON text, in string attributes marked `gravity.type="json"`.
+// Dynamic attributes (spells) are not supported.
+
+use super::attributes::{declare, escape, typed_value, value_text, AttributeMapping, Attributes, xml_type, JSON, JSON_MARKER};
+use super::{read_file, write_file, ImportDefaults};
+use crate::error::PhysicsError;
+use crate::graph::Graph;
+use crate::physics_engine::Link;
+use roxmltree::Document;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+// `<meta>` children kept as graph metadata
+const META: [&str; 3] = ["creator", "description", "keywords"];
+
+fn error(message: String) -> PhysicsError {
+    PhysicsError::Parse { format: "GEXF", message }
+}
+
+// A declared `<attribute>`
+struct Attribute<'a> {
+    id: &'a str,
+    title: &'a str,
+    kind: &'a str,
+    default: Option<&'a str>,
+}
+
+// Parses and validates a GEXF document. Edges are one-way if they, or the
+// graph's `defaultedgetype`, say directed.
+pub fn from_str(input: &str, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    let document = Document::parse(input).map_err(|e| error(e.to_string()))?;
+    let root = document.root_element();
+    if !root.has_tag_name("gexf") {
+        return Err(error(format!("expected <gexf>, found <{}>", root.tag_name().name())));
+    }
+    let graph = child(root, "graph").ok_or_else(|| error("missing <graph>".to_string()))?;
+    let directed = graph.attribute("defaultedgetype") == Some("directed");
+
+    let node_attributes = declarations(graph, "node").map_err(error)?;
+    let edge_attributes = declarations(graph, "edge").map_err(error)?;
+
+    let mut nodes = Vec::new();
+    for element in child(graph, "nodes").iter().flat_map(|nodes| nodes.children()) {
+        if !element.has_tag_name("node") {
+            continue;
+        }
+        let node = element.attribute("id").ok_or("<node> without an id".to_string()).and_then(|id| {
+            let mut attributes = Attributes::new();
+            if let Some(label) = element.attribute("label") {
+                attributes.push((mapping.label.clone(), label.into()));
+            }
+            if let Some(position) = child(element, "position") {
+                let x = position.attribute("x").and_then(|x| x.parse::<f64>().ok());
+                let y = position.attribute("y").and_then(|y| y.parse::<f64>().ok());
+                if let (Some(x), Some(y)) = (x, y) {
+                    attributes.push((mapping.x.clone(), x.into()));
+                    attributes.push((mapping.y.clone(), (-y).into()));
+                }
+            }
+            if let Some(size) = child(element, "size").and_then(|size| size.attribute("value")?.parse::<f64>().ok()) {
+                attributes.push((mapping.radius.clone(), size.into()));
+            }
+            attributes.extend(values(element, &node_attributes)?);
+            mapping.node(id.to_string(), attributes, defaults)
+        });
+        nodes.push(node.map_err(|message| error(format!("node {}: {}", nodes.len(), message)))?);
+    }
+
+    let mut links = Vec::new();
+    for element in child(graph, "edges").iter().flat_map(|edges| edges.children()) {
+        if !element.has_tag_name("edge") {
+            continue;
+        }
+        let link = read_edge(element, directed, &edge_attributes, mapping, defaults);
+        links.push(link.map_err(|message| error(format!("link {}: {}", links.len(), message)))?);
+    }
+
+    let metadata: BTreeMap<String, Value> = child(root, "meta")
+        .iter()
+        .flat_map(|meta| meta.children())
+        .filter(|entry| META.contains(&entry.tag_name().name()))
+        .map(|entry| (entry.tag_name().name().to_string(), entry.text().unwrap_or("").trim().into()))
+        .collect();
+
+    let graph = Graph { nodes, links, metadata };
+    graph.validate()?;
+    Ok(graph)
+}
+
+fn read_edge(
+    element: roxmltree::Node,
+    directed: bool,
+    declared: &[Attribute],
+    mapping: &AttributeMapping,
+    defaults: &ImportDefaults,
+) -> Result<Link, String> {
+    let (Some(source), Some(target)) = (element.attribute("source"), element.attribute("target")) else {
+        return Err("<edge> needs a source and a target".to_string());
+    };
+    let one_way = element.attribute("type").map_or(directed, |kind| kind == "directed");
+
+    let mut attributes = Attributes::new();
+    if let Some(label) = element.attribute("label") {
+        attributes.push(("label".to_string(), label.into()));
+    }
+    if let Some(weight) = element.attribute("weight") {
+        let weight = typed_value("double", weight).map_err(|e| format!("weight: {}", e))?;
+        attributes.push(("weight".to_string(), weight));
+    }
+    attributes.extend(values(element, declared)?);
+    mapping.link(source.to_string(), target.to_string(), !one_way, attributes, defaults)
+}
+
+fn child<'a, 'input>(element: roxmltree::Node<'a, 'input>, name: &str) -> Option<roxmltree::Node<'a, 'input>> {
+    element.children().find(|child| child.has_tag_name(name))
+}
+
+// Attributes declared for a class ("node" or "edge")
+fn declarations<'a>(graph: roxmltree::Node<'a, '_>, class: &str) -> Result<Vec<Attribute<'a>>, String> {
+    graph
+        .children()
+        .filter(|child| child.has_tag_name("attributes") && child.attribute("class") == Some(class))
+        .flat_map(|attributes| attributes.children())
+        .filter(|child| child.has_tag_name("attribute"))
+        .map(|attribute| {
+            let id = attribute.attribute("id").ok_or("<attribute> without an id")?;
+            Ok(Attribute {
+                id,
+                title: attribute.attribute("title").unwrap_or(id),
+                kind: match attribute.attribute(JSON_MARKER) {
+                    Some(JSON) => JSON,
+                    _ => attribute.attribute("type").unwrap_or("string"),
+                },
+                default: child(attribute, "default").map(|default| default.text().unwrap_or("")),
+            })
+        })
+        .collect()
+}
+
+// Typed `<attvalue>`s of an element, preceded by the defaults of the
+// attributes it has no value for
+fn values(element: roxmltree::Node, declared: &[Attribute]) -> Result<Attributes, String> {
+    let mut present = Vec::new();
+    let mut attributes = Attributes::new();
+    let attvalues = child(element, "attvalues");
+    for attvalue in attvalues.iter().flat_map(|values| values.children()).filter(|child| child.has_tag_name("attvalue")) {
+        // GEXF 1.1 called `for` `id`
+        let id = attvalue.attribute("for").or_else(|| attvalue.attribute("id")).ok_or("<attvalue> without a for")?;
+        let attribute = declared
+            .iter()
+            .find(|attribute| attribute.id == id)
+            .ok_or_else(|| format!("undeclared attribute \"{}\"", id))?;
+        let text = attvalue.attribute("value").unwrap_or("");
+        let value = typed_value(attribute.kind, text).map_err(|e| format!("{}: {}", attribute.title, e))?;
+        present.push(id);
+        attributes.push((attribute.title.to_string(), value));
+    }
+
+    let mut defaults = Attributes::new();
+    for attribute in declared.iter().filter(|attribute| !present.contains(&attribute.id)) {
+        if let Some(default) = attribute.default {
+            let value = typed_value(attribute.kind, default).map_err(|e| format!("{}: {}", attribute.title, e))?;
+            defaults.push((attribute.title.to_string(), value));
+        }
+    }
+    defaults.extend(attributes);
+    Ok(defaults)
+}
+
+// GEXF 1.3 document for a graph, with the current node positions as
+// `viz:position` (and the radius as `viz:size`) so Gephi opens it laid out.
+// The graph is directed if any of its links is one-way. String entries of
+// the metadata named in META are written to `<meta>`; other graph metadata
+// has no place in GEXF and is dropped.
+pub fn to_string(graph: &Graph, mapping: &AttributeMapping) -> String {
+    let directed = graph.links.iter().any(|link| !link.physical_properties.bidirectional);
+
+    // Label and position have their own markup
+    let node_attributes: Vec<Attributes> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            let mut attributes = mapping.node_attributes(node);
+            attributes.retain(|(name, _)| *name != mapping.label && *name != mapping.x && *name != mapping.y);
+            attributes
+        })
+        .collect();
+    let link_attributes: Vec<Attributes> = graph.links.iter().map(|link| mapping.link_attributes(link)).collect();
+
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <gexf xmlns=\"http://gexf.net/1.3\" xmlns:viz=\"http://gexf.net/1.3/viz\" version=\"1.3\">\n",
+    );
+    let meta: Vec<(&str, &str)> = META
+        .iter()
+        .filter_map(|name| Some((*name, graph.metadata.get(*name)?.as_str()?)))
+        .collect();
+    if !meta.is_empty() {
+        out.push_str("  <meta>\n");
+        for (name, text) in meta {
+            let _ = writeln!(out, "    <{0}>{1}</{0}>", name, escape(text));
+        }
+        out.push_str("  </meta>\n");
+    }
+
+    let edge_type = if directed { "directed" } else { "undirected" };
+    let _ = writeln!(out, "  <graph mode=\"static\" defaultedgetype=\"{}\">", edge_type);
+
+    let mut attribute_ids: Vec<BTreeMap<String, String>> = Vec::new();
+    for (class, rows) in [("node", &node_attributes), ("edge", &link_attributes)] {
+        let declared = declare(rows);
+        let mut ids = BTreeMap::new();
+        if !declared.is_empty() {
+            let _ = writeln!(out, "    <attributes class=\"{}\" mode=\"static\">", class);
+            for (i, (title, kind)) in declared.into_iter().enumerate() {
+                let (kind, marker) = xml_type(kind);
+                let _ = writeln!(
+                    out,
+                    "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"{}/>",
+                    i,
+                    escape(&title),
+                    kind,
+                    marker
+                );
+                ids.insert(title, i.to_string());
+            }
+            out.push_str("    </attributes>\n");
+        }
+        attribute_ids.push(ids);
+    }
+
+    out.push_str("    <nodes>\n");
+    for (node, attributes) in graph.nodes.iter().zip(&node_attributes) {
+        let _ = writeln!(out, "      <node id=\"{}\" label=\"{}\">", escape(&node.id), escape(&node.label));
+        write_values(&mut out, attributes, &attribute_ids[0]);
+        let _ = writeln!(out, "        <viz:position x=\"{}\" y=\"{}\" z=\"0.0\"/>", node.x, 0.0 - node.y);
+        let _ = writeln!(out, "        <viz:size value=\"{}\"/>", node.physical_properties.radius);
+        out.push_str("      </node>\n");
+    }
+    out.push_str("    </nodes>\n    <edges>\n");
+    for (i, (link, attributes)) in graph.links.iter().zip(&link_attributes).enumerate() {
+        let kind = if link.physical_properties.bidirectional { "undirected" } else { "directed" };
+        let _ = writeln!(
+            out,
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\" type=\"{}\">",
+            i,
+            escape(&link.source),
+            escape(&link.target),
+            kind
+        );
+        write_values(&mut out, attributes, &attribute_ids[1]);
+        out.push_str("      </edge>\n");
+    }
+    out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    out
+}
+
+fn write_values(out: &mut String, attributes: &Attributes, ids: &BTreeMap<String, String>) {
+    let mut attributes = attributes.iter().filter(|(_, value)| !value.is_null()).peekable();
+    if attributes.peek().is_none() {
+        return;
+    }
+    out.push_str("        <attvalues>\n");
+    for (title, value) in attributes {
+        let _ = writeln!(
+            out,
+            "          <attvalue for=\"{}\" value=\"{}\"/>",
+            ids[title],
+            escape(&value_text(value))
+        );
+    }
+    out.push_str("        </attvalues>\n");
+}
+
+pub fn read(path: impl AsRef<Path>, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    from_str(&read_file(path.as_ref())?, mapping, defaults)
+}
+
+pub fn write(path: impl AsRef<Path>, graph: &Graph, mapping: &AttributeMapping) -> Result<(), PhysicsError> {
+    write_file(path.as_ref(), &to_string(graph, mapping))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::Position;
+
+    const GRAPH: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">
+          <meta><creator>Gephi 0.10</creator><description>Patterns</description></meta>
+          <graph defaultedgetype="undirected">
+            <attributes class="node">
+              <attribute id="0" title="mass" type="double"><default>3</default></attribute>
+            </attributes>
+            <nodes>
+              <node id="feedback-loops" label="Feedback Loops">
+                <attvalues><attvalue for="0" value="5"/></attvalues>
+                <viz:position x="12.5" y="-40" z="0"/>
+                <viz:size value="8"/>
+              </node>
+              <node id="homeostasis" label="Homeostasis"/>
+            </nodes>
+            <edges>
+              <edge id="0" source="feedback-loops" target="homeostasis" type="directed" weight="2.5" label="regulates"/>
+              <edge id="1" source="homeostasis" target="feedback-loops"/>
+            </edges>
+          </graph>
+        </gexf>"#;
+
+    #[test]
+    fn viz_position_and_size_place_and_size_the_node() {
+        let mapping = AttributeMapping::default();
+        let graph = from_str(GRAPH, &mapping, &ImportDefaults::default()).unwrap();
+
+        // Gephi's y axis points up, the engine's down
+        let node = &graph.nodes[0];
+        assert_eq!((node.x, node.y), (12.5, 40.0));
+        assert_eq!(node.physical_properties.initial_position, Some(Position { x: 12.5, y: 40.0 }));
+        assert_eq!(node.physical_properties.radius, 8.0);
+        assert_eq!(node.label, "Feedback Loops");
+        // The attribute default fills in for the node without a value
+        assert_eq!(node.physical_properties.mass, 5.0);
+        assert_eq!(graph.nodes[1].physical_properties.mass, 3.0);
+
+        let text = to_string(&graph, &mapping);
+        assert!(text.contains(r#"<node id="feedback-loops" label="Feedback Loops">"#), "{}", text);
+        assert!(text.contains(r#"<viz:position x="12.5" y="-40" z="0.0"/>"#));
+        assert!(text.contains(r#"<viz:size value="8"/>"#));
+        // Position has its own markup, not attributes
+        assert!(!text.contains(r#"title="x""#) && !text.contains(r#"title="y""#));
+        assert_eq!(from_str(&text, &mapping, &ImportDefaults::default()).unwrap().nodes[0].y, 40.0);
+    }
+
+    #[test]
+    fn edge_weight_label_and_type_are_read() {
+        let graph = from_str(GRAPH, &AttributeMapping::default(), &ImportDefaults::default()).unwrap();
+        let link = &graph.links[0];
+        assert_eq!(link.metadata["weight"], 2.5);
+        assert_eq!(link.metadata["label"], "regulates");
+        // `type` on the edge overrides the graph's defaultedgetype
+        assert!(!link.physical_properties.bidirectional);
+        assert!(graph.links[1].physical_properties.bidirectional);
+        assert_eq!(graph.metadata["creator"], "Gephi 0.10");
+        assert_eq!(graph.metadata["description"], "Patterns");
+
+        // Mapped, the weight becomes the spring stiffness
+        let mapping = AttributeMapping { stiffness: "weight".to_string(), ..Default::default() };
+        let graph = from_str(GRAPH, &mapping, &ImportDefaults::default()).unwrap();
+        assert_eq!(graph.links[0].physical_properties.stiffness, 2.5);
+        assert!(!graph.links[0].metadata.contains_key("weight"));
+
+        let text = to_string(&graph, &mapping);
+        assert!(text.contains("<creator>Gephi 0.10</creator>"), "{}", text);
+        assert!(text.contains(r#"<edge id="0" source="feedback-loops" target="homeostasis" type="directed">"#));
+        assert!(text.contains(r#"<edge id="1" source="homeostasis" target="feedback-loops" type="undirected">"#));
+    }
+}
@@ -0,0 +1,351 @@
+// GraphML, as written by yEd, Gephi, NetworkX (`write_graphml`) and igraph:
+//
+//   <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+//     <key id="d0" for="node" attr.name="mass" attr.type="double"/>
+//     <graph edgedefault="undirected">
+//       <node id="feedback-loops"><data key="d0">5</data></node>
+//       <edge source="feedback-loops" target="homeostasis"/>
+//     </graph>
+//   </graphml>
+//
+// Typed `<key>` attributes are mapped onto Node / Link through an
+// AttributeMapping; unmapped ones are kept in `metadata`, as are attributes
+// of the graph itself. Lists and objects are written as JSON text, on keys
+// marked `gravity.type="json"`. yEd node graphics (`y:Geometry`, `y:NodeLabel`)
+// supply a position and label when no mapped attribute does. Nested graphs
+// and hyperedges are not supported.
+
+use super::attributes::{declare, escape, typed_value, value_text, AttributeMapping, Attributes, xml_type, JSON, JSON_MARKER};
+use super::{read_file, write_file, ImportDefaults};
+use crate::error::PhysicsError;
+use crate::graph::Graph;
+use roxmltree::{Document, ParsingOptions};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+fn error(message: String) -> PhysicsError {
+    PhysicsError::Parse {
+        format: "GraphML",
+        message,
+    }
+}
+
+// A declared `<key>`
+struct Key<'a> {
+    id: &'a str,
+    domain: &'a str,
+    name: &'a str,
+    kind: &'a str,
+    default: Option<&'a str>,
+}
+
+impl Key<'_> {
+    fn applies_to(&self, domain: &str) -> bool {
+        self.domain == domain || self.domain == "all"
+    }
+}
+
+// Parses and validates a GraphML document; the first `<graph>` is read.
+// Edges are one-way if they, or the graph's `edgedefault`, say directed.
+pub fn from_str(input: &str, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = Document::parse_with_options(input, options).map_err(|e| error(e.to_string()))?;
+    let root = document.root_element();
+    if !root.has_tag_name("graphml") {
+        return Err(error(format!("expected <graphml>, found <{}>", root.tag_name().name())));
+    }
+
+    let keys: Vec<Key> = root
+        .children()
+        .filter(|child| child.has_tag_name("key"))
+        .map(|key| {
+            let id = key.attribute("id").ok_or_else(|| error("<key> without an id".to_string()))?;
+            Ok(Key {
+                id,
+                domain: key.attribute("for").unwrap_or("all"),
+                name: key.attribute("attr.name").unwrap_or(id),
+                kind: match key.attribute(JSON_MARKER) {
+                    Some(JSON) => JSON,
+                    _ => key.attribute("attr.type").unwrap_or("string"),
+                },
+                default: key
+                    .children()
+                    .find(|child| child.has_tag_name("default"))
+                    .map(|default| default.text().unwrap_or("")),
+            })
+        })
+        .collect::<Result<_, PhysicsError>>()?;
+
+    let graph = root
+        .children()
+        .find(|child| child.has_tag_name("graph"))
+        .ok_or_else(|| error("missing <graph>".to_string()))?;
+    let directed = graph.attribute("edgedefault") == Some("directed");
+
+    let mut nodes = Vec::new();
+    let mut links = Vec::new();
+    for element in graph.children().filter(|child| child.is_element()) {
+        match element.tag_name().name() {
+            "node" => {
+                let id = element.attribute("id").ok_or("<node> without an id".to_string());
+                let node = id.and_then(|id| {
+                    let attributes = yed_graphics(element, mapping)
+                        .into_iter()
+                        .chain(data(element, &keys, "node")?)
+                        .collect();
+                    mapping.node(id.to_string(), attributes, defaults)
+                });
+                nodes.push(node.map_err(|message| error(format!("node {}: {}", nodes.len(), message)))?);
+            }
+            "edge" => {
+                let link = match (element.attribute("source"), element.attribute("target")) {
+                    (Some(source), Some(target)) => {
+                        let one_way = element.attribute("directed").map_or(directed, |flag| flag == "true");
+                        data(element, &keys, "edge").and_then(|attributes| {
+                            mapping.link(source.to_string(), target.to_string(), !one_way, attributes, defaults)
+                        })
+                    }
+                    _ => Err("<edge> needs a source and a target".to_string()),
+                };
+                links.push(link.map_err(|message| error(format!("link {}: {}", links.len(), message)))?);
+            }
+            _ => {}
+        }
+    }
+
+    let metadata: BTreeMap<_, _> = data(graph, &keys, "graph").map_err(error)?.into_iter().collect();
+    let graph = Graph { nodes, links, metadata };
+    graph.validate()?;
+    Ok(graph)
+}
+
+// Typed `<data>` values of an element, preceded by the defaults of the keys
+// it has no data for
+fn data(element: roxmltree::Node, keys: &[Key], domain: &str) -> Result<Attributes, String> {
+    let mut present = Vec::new();
+    let mut attributes = Attributes::new();
+    for data in element.children().filter(|child| child.has_tag_name("data")) {
+        let id = data.attribute("key").ok_or("<data> without a key")?;
+        let key = keys.iter().find(|key| key.id == id).ok_or_else(|| format!("undeclared key \"{}\"", id))?;
+        present.push(id);
+        // Values of yEd's graphics keys are markup, not text
+        if data.children().any(|child| child.is_element()) {
+            continue;
+        }
+        let value = typed_value(key.kind, data.text().unwrap_or("")).map_err(|e| format!("{}: {}", key.name, e))?;
+        attributes.push((key.name.to_string(), value));
+    }
+
+    let mut defaults = Attributes::new();
+    for key in keys.iter().filter(|key| key.applies_to(domain) && !present.contains(&key.id)) {
+        if let Some(default) = key.default {
+            let value = typed_value(key.kind, default).map_err(|e| format!("{}: {}", key.name, e))?;
+            defaults.push((key.name.to_string(), value));
+        }
+    }
+    defaults.extend(attributes);
+    Ok(defaults)
+}
+
+// Label and centre of a yEd node (`<y:ShapeNode>` and friends). yEd's y axis
+// points down, like the engine's.
+fn yed_graphics(element: roxmltree::Node, mapping: &AttributeMapping) -> Attributes {
+    let mut attributes = Attributes::new();
+    let geometry = element.descendants().find(|child| child.has_tag_name("Geometry"));
+    let number = |name| geometry.and_then(|geometry| geometry.attribute(name)?.parse::<f64>().ok());
+    if let (Some(x), Some(y)) = (number("x"), number("y")) {
+        let (width, height) = (number("width").unwrap_or(0.0), number("height").unwrap_or(0.0));
+        attributes.push((mapping.x.clone(), (x + width / 2.0).into()));
+        attributes.push((mapping.y.clone(), (y + height / 2.0).into()));
+    }
+    let label = element.descendants().find(|child| child.has_tag_name("NodeLabel"));
+    if let Some(label) = label.and_then(|label| label.text()).map(str::trim).filter(|label| !label.is_empty()) {
+        attributes.push((mapping.label.clone(), label.into()));
+    }
+    attributes
+}
+
+// GraphML document for a graph, with the current node positions as the
+// mapped x/y attributes. The graph is directed if any of its links is
+// one-way; two-way links of a directed graph are marked `directed="false"`.
+pub fn to_string(graph: &Graph, mapping: &AttributeMapping) -> String {
+    let directed = graph.links.iter().any(|link| !link.physical_properties.bidirectional);
+    let graph_attributes: Attributes = graph.metadata.clone().into_iter().collect();
+    let node_attributes: Vec<Attributes> = graph.nodes.iter().map(|node| mapping.node_attributes(node)).collect();
+    let link_attributes: Vec<Attributes> = graph.links.iter().map(|link| mapping.link_attributes(link)).collect();
+
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns \
+         http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n",
+    );
+
+    // Key ids are g0.., n0.., e0.. by domain
+    let mut key_ids: Vec<BTreeMap<String, String>> = Vec::new();
+    for (prefix, domain, rows) in [
+        ("g", "graph", std::slice::from_ref(&graph_attributes)),
+        ("n", "node", &node_attributes[..]),
+        ("e", "edge", &link_attributes[..]),
+    ] {
+        let mut ids = BTreeMap::new();
+        for (i, (name, kind)) in declare(rows).into_iter().enumerate() {
+            let id = format!("{}{}", prefix, i);
+            let (kind, marker) = xml_type(kind);
+            let _ = writeln!(
+                out,
+                "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"{}/>",
+                id,
+                domain,
+                escape(&name),
+                kind,
+                marker
+            );
+            ids.insert(name, id);
+        }
+        key_ids.push(ids);
+    }
+
+    let edgedefault = if directed { "directed" } else { "undirected" };
+    let _ = writeln!(out, "  <graph id=\"G\" edgedefault=\"{}\">", edgedefault);
+    write_data(&mut out, "    ", &graph_attributes, &key_ids[0]);
+    for (node, attributes) in graph.nodes.iter().zip(&node_attributes) {
+        let _ = writeln!(out, "    <node id=\"{}\">", escape(&node.id));
+        write_data(&mut out, "      ", attributes, &key_ids[1]);
+        out.push_str("    </node>\n");
+    }
+    for (link, attributes) in graph.links.iter().zip(&link_attributes) {
+        let two_way = if directed && link.physical_properties.bidirectional { " directed=\"false\"" } else { "" };
+        let _ = writeln!(
+            out,
+            "    <edge source=\"{}\" target=\"{}\"{}>",
+            escape(&link.source),
+            escape(&link.target),
+            two_way
+        );
+        write_data(&mut out, "      ", attributes, &key_ids[2]);
+        out.push_str("    </edge>\n");
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+fn write_data(out: &mut String, indent: &str, attributes: &Attributes, key_ids: &BTreeMap<String, String>) {
+    for (name, value) in attributes.iter().filter(|(_, value)| !value.is_null()) {
+        let _ = writeln!(out, "{}<data key=\"{}\">{}</data>", indent, key_ids[name], escape(&value_text(value)));
+    }
+}
+
+pub fn read(path: impl AsRef<Path>, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    from_str(&read_file(path.as_ref())?, mapping, defaults)
+}
+
+pub fn write(path: impl AsRef<Path>, graph: &Graph, mapping: &AttributeMapping) -> Result<(), PhysicsError> {
+    write_file(path.as_ref(), &to_string(graph, mapping))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::networkx;
+    use crate::physics_engine::Position;
+
+    #[test]
+    fn yed_graphics_give_the_centre_and_label() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:y="http://www.yworks.com/xml/graphml">
+              <key id="d0" for="node" yfiles.type="nodegraphics"/>
+              <key id="d1" for="node" attr.name="label" attr.type="string"/>
+              <graph edgedefault="undirected">
+                <node id="n0">
+                  <data key="d0">
+                    <y:ShapeNode>
+                      <y:Geometry x="100.0" y="50.0" width="30.0" height="20.0"/>
+                      <y:NodeLabel> Feedback Loops </y:NodeLabel>
+                    </y:ShapeNode>
+                  </data>
+                </node>
+                <node id="n1">
+                  <data key="d0">
+                    <y:ShapeNode>
+                      <y:Geometry x="0.0" y="0.0" width="30.0" height="30.0"/>
+                      <y:NodeLabel>yEd label</y:NodeLabel>
+                    </y:ShapeNode>
+                  </data>
+                  <data key="d1">Homeostasis</data>
+                </node>
+              </graph>
+            </graphml>"#;
+        let graph = from_str(input, &AttributeMapping::default(), &ImportDefaults::default()).unwrap();
+
+        // y:Geometry is the bounding box; the node sits at its centre
+        let placed = &graph.nodes[0];
+        assert_eq!((placed.x, placed.y), (115.0, 60.0));
+        assert_eq!(placed.physical_properties.initial_position, Some(Position { x: 115.0, y: 60.0 }));
+        assert_eq!(placed.label, "Feedback Loops");
+        // A mapped attribute wins over the graphics
+        assert_eq!(graph.nodes[1].label, "Homeostasis");
+        assert!(placed.metadata.is_empty());
+    }
+
+    #[test]
+    fn key_defaults_fill_in_missing_data_and_edges_override_edgedefault() {
+        let input = r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+              <key id="m" for="node" attr.name="mass" attr.type="double"><default>3</default></key>
+              <key id="t" for="all" attr.name="tag" attr.type="string"><default>core</default></key>
+              <key id="w" for="edge" attr.name="weight" attr.type="int"/>
+              <graph edgedefault="directed">
+                <node id="a"><data key="m">7</data></node>
+                <node id="b"><data key="t">edge-case</data></node>
+                <edge source="a" target="b"><data key="w">2</data></edge>
+                <edge source="b" target="a" directed="false"/>
+              </graph>
+            </graphml>"#;
+        let graph = from_str(input, &AttributeMapping::default(), &ImportDefaults::default()).unwrap();
+
+        assert_eq!(graph.nodes[0].physical_properties.mass, 7.0);
+        assert_eq!(graph.nodes[1].physical_properties.mass, 3.0);
+        assert_eq!(graph.nodes[0].metadata["tag"], "core");
+        assert_eq!(graph.nodes[1].metadata["tag"], "edge-case");
+        // `for="all"` defaults apply to edges and the graph too
+        assert_eq!(graph.links[1].metadata["tag"], "core");
+        assert_eq!(graph.metadata["tag"], "core");
+        assert_eq!(graph.links[0].metadata["weight"], 2);
+        assert!(!graph.links[0].physical_properties.bidirectional);
+        assert!(graph.links[1].physical_properties.bidirectional);
+    }
+
+    #[test]
+    fn lists_and_objects_round_trip_on_json_keys() {
+        let input = serde_json::json!({
+            "directed": true,
+            "nodes": [
+                { "id": "feedback-loops", "domains": ["Nature", "Society"], "source": { "file": "feedback-loops.md" } },
+                { "id": "homeostasis", "domains": [] },
+            ],
+            "links": [
+                { "source": "feedback-loops", "target": "homeostasis", "type": "causal" },
+                { "source": "homeostasis", "target": "feedback-loops", "type": "causal", "bidirectional": true },
+            ],
+        });
+        let mapping = AttributeMapping::default();
+        let defaults = ImportDefaults::default();
+        let graph = networkx::from_value(&input, &mapping, &defaults).unwrap();
+
+        let text = to_string(&graph, &mapping);
+        assert!(text.contains(r#"attr.name="domains" attr.type="string" gravity.type="json"/>"#), "{}", text);
+        assert!(text.contains(r#"<edge source="homeostasis" target="feedback-loops" directed="false">"#));
+
+        let read = from_str(&text, &mapping, &defaults).unwrap();
+        assert_eq!(read.nodes[0].metadata["domains"], input["nodes"][0]["domains"]);
+        assert_eq!(read.nodes[0].metadata["source"], input["nodes"][0]["source"]);
+        assert_eq!(read.nodes[1].metadata["domains"], serde_json::json!([]));
+        assert!(!read.links[0].physical_properties.bidirectional && read.links[1].physical_properties.bidirectional);
+        assert_eq!(to_string(&read, &mapping), text);
+    }
+}
@@ -0,0 +1,114 @@
+// Native JSON graph format: `{ "nodes": [Node], "links": [Link] }` with the
+// serde field names of `Node` and `Link`. The camelCase names used by the
+// JavaScript engine (`physicalProperties`, `initialPosition`, ...) are
+// accepted as aliases. A node's `x`/`y` is its initial position unless it
+// has an explicit `initial_position`, so a written layout reads back in place.
+// Links without `bidirectional` are one-way if their relationship type is.
+
+use super::{read_file, write_file};
+use crate::error::PhysicsError;
+use crate::graph::Graph;
+use crate::physics_engine::Position;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+// Parses and validates a graph
+pub fn from_str(input: &str) -> Result<Graph, PhysicsError> {
+    let parse_error = |error: serde_json::Error| PhysicsError::Parse {
+        format: "JSON graph",
+        message: error.to_string(),
+    };
+    let value: Value = serde_json::from_str(input).map_err(parse_error)?;
+    let mut graph: Graph = Graph::deserialize(&value).map_err(parse_error)?;
+
+    let written = value.get("nodes").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    for (node, written) in graph.nodes.iter_mut().zip(written) {
+        let placed = written.get("x").is_some() || written.get("y").is_some();
+        if placed && node.physical_properties.initial_position.is_none() {
+            node.physical_properties.initial_position = Some(Position { x: node.x, y: node.y });
+        }
+    }
+
+    let written = ["links", "edges"]
+        .iter()
+        .find_map(|key| value.get(key).and_then(Value::as_array))
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for (link, written) in graph.links.iter_mut().zip(written) {
+        let properties = written.get("physical_properties").or_else(|| written.get("physicalProperties"));
+        if properties.and_then(|properties| properties.get("bidirectional")).is_none() {
+            link.physical_properties.bidirectional = !link.relationship_type.is_one_way();
+        }
+    }
+
+    graph.validate()?;
+    Ok(graph)
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<Graph, PhysicsError> {
+    from_str(&read_file(path.as_ref())?)
+}
+
+pub fn to_string(graph: &Graph) -> String {
+    serde_json::to_string_pretty(graph).expect("graphs always serialize to JSON")
+}
+
+pub fn write(path: impl AsRef<Path>, graph: &Graph) -> Result<(), PhysicsError> {
+    write_file(path.as_ref(), &to_string(graph))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::{PhysicsEngine, PhysicsOptions};
+
+    const GRAPH: &str = r#"{
+        "nodes": [
+            { "id": "a", "physical_properties": { "mass": 1, "charge": -30, "friction": 0.1, "radius": 10 } },
+            { "id": "b", "physical_properties": { "mass": 1, "charge": -30, "friction": 0.1, "radius": 10 } },
+            { "id": "c", "physical_properties": { "mass": 1, "charge": -30, "friction": 0.1, "radius": 10 }, "x": 5 }
+        ],
+        "links": [
+            { "source": "a", "target": "b", "physical_properties": { "stiffness": 0.1, "length": 50, "elasticity": 0.5 } }
+        ]
+    }"#;
+
+    #[test]
+    fn written_layout_reads_back_in_place() {
+        let graph = from_str(GRAPH).unwrap();
+        assert!(graph.nodes[0].physical_properties.initial_position.is_none());
+        assert_eq!(graph.nodes[2].physical_properties.initial_position, Some(Position { x: 5.0, y: 0.0 }));
+
+        let options = PhysicsOptions { seed: Some(7), ..Default::default() };
+        let mut engine = PhysicsEngine::new(graph.nodes, graph.links, Some(options.clone())).unwrap();
+        for _ in 0..20 {
+            engine.tick().unwrap();
+        }
+
+        let read = from_str(&to_string(&engine.graph())).unwrap();
+        let seed = PhysicsOptions { seed: Some(8), ..options };
+        let reloaded = PhysicsEngine::new(read.nodes, read.links, Some(seed)).unwrap();
+        for (before, after) in engine.nodes.iter().zip(&reloaded.nodes) {
+            assert_eq!((before.x, before.y), (after.x, after.y));
+        }
+    }
+
+    #[test]
+    fn links_without_a_direction_take_it_from_their_type() {
+        let mut value: Value = serde_json::from_str(GRAPH).unwrap();
+        let link = value["links"][0].clone();
+        let typed = |relationship: &str| {
+            let mut link = link.clone();
+            link["relationship_type"] = Value::from(relationship);
+            link
+        };
+        let mut explicit = typed("causal");
+        explicit["physical_properties"]["bidirectional"] = Value::from(true);
+        value["links"] = Value::from(vec![typed("causal"), typed("feedback"), typed("hierarchical"), typed("strong"), explicit]);
+
+        let graph = from_str(&value.to_string()).unwrap();
+        let bidirectional: Vec<bool> = graph.links.iter().map(|link| link.physical_properties.bidirectional).collect();
+        assert_eq!(bidirectional, [false, false, false, true, true]);
+    }
+}
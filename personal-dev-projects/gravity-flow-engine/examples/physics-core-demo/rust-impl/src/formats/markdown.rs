@@ -0,0 +1,288 @@
+// Importer for the Markdown pattern collection
+// (complexity-science/.../data/patterns/*.md). Each file describes one
+// pattern:
+//
+//   ---
+//   category: process          (optional front matter)
+//   ---
+//   # Feedback Loops
+//   ## Category                (optional, first line is the category)
+//   ## Domain(s)
+//   - Nature (Physical Systems, Biological Systems)
+//   ## Related Patterns
+//   - Homeostasis
+//
+// Related-pattern bullets become links to the patterns of the same
+// collection; names that match no pattern are skipped.
+
+use super::{read_file, ImportDefaults};
+use crate::error::PhysicsError;
+use crate::graph::Graph;
+use crate::physics_engine::{Link, Node, RelationshipType};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+// One parsed pattern file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pattern {
+    pub name: String,
+    pub category: Option<String>,
+    pub domains: Vec<String>,
+    pub related: Vec<String>,
+    // Front matter entries other than the category
+    pub front_matter: BTreeMap<String, String>,
+    // Body of every other `##` section, by heading
+    pub sections: BTreeMap<String, String>,
+}
+
+impl Pattern {
+    // Node id, as in the architecture doc: the name lowercased with runs of
+    // whitespace replaced by '-'
+    pub fn id(&self) -> String {
+        pattern_id(&self.name)
+    }
+}
+
+pub fn pattern_id(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase()
+}
+
+#[derive(Clone, Debug)]
+pub struct PatternImport {
+    // Category by pattern id, for files that don't declare one
+    pub categories: HashMap<String, String>,
+    // Category of patterns that declare none and aren't in `categories`
+    pub default_category: String,
+    pub defaults: ImportDefaults,
+}
+
+impl Default for PatternImport {
+    fn default() -> Self {
+        PatternImport {
+            categories: HashMap::new(),
+            default_category: "uncategorized".to_string(),
+            defaults: ImportDefaults::default(),
+        }
+    }
+}
+
+pub fn parse_pattern(text: &str) -> Pattern {
+    let mut pattern = Pattern::default();
+    let mut lines = text.lines().peekable();
+
+    // Front matter: `key: value` lines between two `---` lines at the top
+    if lines.peek().map(|line| line.trim()) == Some("---") {
+        lines.next();
+        for line in lines.by_ref() {
+            if line.trim() == "---" {
+                break;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim().trim_matches('"').to_string();
+                match key.trim() {
+                    "category" => pattern.category = Some(value),
+                    key => {
+                        pattern.front_matter.insert(key.to_string(), value);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut heading: Option<String> = None;
+    let mut body = String::new();
+    for line in lines {
+        if let Some(name) = line.strip_prefix("# ") {
+            if pattern.name.is_empty() {
+                pattern.name = name.trim().to_string();
+            }
+        } else if let Some(next) = line.strip_prefix("## ") {
+            if let Some(heading) = heading.take() {
+                add_section(&mut pattern, heading, &body);
+            }
+            heading = Some(next.trim().to_string());
+            body.clear();
+        } else if heading.is_some() {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    if let Some(heading) = heading {
+        add_section(&mut pattern, heading, &body);
+    }
+
+    pattern
+}
+
+fn add_section(pattern: &mut Pattern, heading: String, body: &str) {
+    match heading.as_str() {
+        "Domain" | "Domains" | "Domain(s)" => pattern.domains = bullets(body),
+        "Related Patterns" => pattern.related = bullets(body),
+        "Category" => {
+            if pattern.category.is_none() {
+                pattern.category = body
+                    .lines()
+                    .map(|line| line.trim().trim_start_matches("- ").trim())
+                    .find(|line| !line.is_empty())
+                    .map(str::to_string);
+            }
+        }
+        _ => {
+            pattern.sections.insert(heading, body.trim().to_string());
+        }
+    }
+}
+
+// Text of the top-level `- ` bullets of a section
+fn bullets(body: &str) -> Vec<String> {
+    body.lines()
+        .filter_map(|line| line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")))
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// Builds and validates the graph of a pattern collection. A pair of patterns
+// that list each other gets a single link.
+pub fn import_patterns(patterns: &[Pattern], options: &PatternImport) -> Result<Graph, PhysicsError> {
+    let nodes: Vec<Node> = patterns.iter().map(|pattern| pattern_node(pattern, options)).collect();
+    let ids: HashSet<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+
+    let mut linked: HashSet<(String, String)> = HashSet::new();
+    let mut links = Vec::new();
+    for (pattern, node) in patterns.iter().zip(&nodes) {
+        for related in &pattern.related {
+            let target = pattern_id(related);
+            if target == node.id || !ids.contains(target.as_str()) {
+                continue;
+            }
+            if linked.contains(&(target.clone(), node.id.clone())) || !linked.insert((node.id.clone(), target.clone())) {
+                continue;
+            }
+            links.push(Link {
+                source: node.id.clone(),
+                target,
+                relationship_type: RelationshipType::Related,
+                physical_properties: options.defaults.link.clone(),
+                metadata: BTreeMap::new(),
+            });
+        }
+    }
+
+    let graph = Graph { nodes, links, ..Default::default() };
+    graph.validate()?;
+    Ok(graph)
+}
+
+fn pattern_node(pattern: &Pattern, options: &PatternImport) -> Node {
+    let id = pattern.id();
+    let category = pattern
+        .category
+        .clone()
+        .or_else(|| options.categories.get(&id).cloned())
+        .unwrap_or_else(|| options.default_category.clone());
+
+    let mut metadata: BTreeMap<String, Value> = BTreeMap::new();
+    metadata.insert("domains".to_string(), Value::from(pattern.domains.clone()));
+    for (key, value) in &pattern.front_matter {
+        metadata.insert(key.clone(), Value::from(value.as_str()));
+    }
+    for (heading, body) in &pattern.sections {
+        let (key, value) = match heading.as_str() {
+            "Pattern Description" => ("description", Value::from(body.as_str())),
+            "Examples" => ("examples", Value::from(body.as_str())),
+            "Underlying Mechanisms" => ("mechanisms", Value::from(bullets(body))),
+            "Potential Applications" => ("applications", Value::from(bullets(body))),
+            "References" => ("references", Value::from(bullets(body))),
+            other => (other, Value::from(body.as_str())),
+        };
+        metadata.insert(key.to_string(), value);
+    }
+
+    Node {
+        id,
+        label: pattern.name.clone(),
+        category,
+        physical_properties: options.defaults.node.clone(),
+        metadata,
+        x: 0.0,
+        y: 0.0,
+        vx: 0.0,
+        vy: 0.0,
+        fx: None,
+        fy: None,
+    }
+}
+
+// Imports every `.md` file of a directory, in file name order. Files without
+// a `#` title are named after the file.
+pub fn read_dir(path: impl AsRef<Path>, options: &PatternImport) -> Result<Graph, PhysicsError> {
+    let path = path.as_ref();
+    let io_error = |error: std::io::Error| PhysicsError::Io {
+        path: path.display().to_string(),
+        message: error.to_string(),
+    };
+
+    let mut files: Vec<_> = fs::read_dir(path)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()
+        .map_err(io_error)?;
+    files.retain(|file| file.extension().is_some_and(|extension| extension == "md"));
+    files.sort();
+
+    let mut patterns = Vec::with_capacity(files.len());
+    for file in files {
+        let mut pattern = parse_pattern(&read_file(&file)?);
+        if pattern.name.is_empty() {
+            pattern.name = file.file_stem().unwrap_or_default().to_string_lossy().replace('-', " ");
+        }
+        patterns.push(pattern);
+    }
+
+    import_patterns(&patterns, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_come_from_the_file_then_the_options() {
+        let patterns = [
+            parse_pattern("---\ncategory: \"structural\"\nsource: field notes\n---\n# Network Structure\n"),
+            parse_pattern("# Feedback Loops\n\n## Category\n- process\n\n## Related Patterns\n- Cyclical Patterns\n"),
+            parse_pattern("# Cyclical Patterns\n\n## Related Patterns\n- Feedback Loops\n- Self-Organization\n"),
+            parse_pattern("# Something New\n"),
+        ];
+        let mut options = PatternImport::default();
+        options.categories.insert("cyclical-patterns".to_string(), "process".to_string());
+        let graph = import_patterns(&patterns, &options).unwrap();
+
+        let categories: Vec<&str> = graph.nodes.iter().map(|node| node.category.as_str()).collect();
+        assert_eq!(categories, ["structural", "process", "process", "uncategorized"]);
+        assert_eq!(graph.nodes[0].metadata["source"], "field notes");
+        // Patterns that list each other share one link; unknown names are skipped
+        assert_eq!(graph.links.len(), 1);
+    }
+
+    #[test]
+    fn every_collection_pattern_declares_a_category() {
+        let directory = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../../../complexity-science/pattern-recognition/natural-patterns/data/patterns"
+        );
+        let graph = read_dir(directory, &PatternImport::default()).unwrap();
+        assert!(!graph.nodes.is_empty());
+        for node in &graph.nodes {
+            assert!(
+                ["structural", "process", "relationship", "resilience"].contains(&node.category.as_str()),
+                "{} has category \"{}\"",
+                node.id,
+                node.category
+            );
+        }
+    }
+}
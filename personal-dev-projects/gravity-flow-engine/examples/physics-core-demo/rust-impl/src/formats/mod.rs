@@ -0,0 +1,60 @@
+// Reading and writing graphs in external file formats
+
+pub mod dot;
+pub mod gexf;
+pub mod graphml;
+pub mod json;
+pub mod markdown;
+pub mod networkx;
+mod attributes;
+
+pub use attributes::AttributeMapping;
+
+use crate::error::PhysicsError;
+use crate::physics_engine::{LinkPhysicalProperties, PhysicalProperties, SpringModel};
+use std::fs;
+use std::path::Path;
+
+// Physics given to imported nodes and links whose file doesn't specify it
+#[derive(Clone, Debug)]
+pub struct ImportDefaults {
+    pub node: PhysicalProperties,
+    pub link: LinkPhysicalProperties,
+}
+
+impl Default for ImportDefaults {
+    // The physics of the demo's process patterns and regular relationships
+    fn default() -> Self {
+        ImportDefaults {
+            node: PhysicalProperties {
+                mass: 5.0,
+                charge: -130.0,
+                friction: 0.3,
+                radius: 15.0,
+                fixed: false,
+                initial_position: None,
+            },
+            link: LinkPhysicalProperties {
+                stiffness: 0.3,
+                length: 100.0,
+                elasticity: 0.5,
+                spring_model: SpringModel::Linear,
+                bidirectional: true,
+            },
+        }
+    }
+}
+
+pub(crate) fn read_file(path: &Path) -> Result<String, PhysicsError> {
+    fs::read_to_string(path).map_err(|error| PhysicsError::Io {
+        path: path.display().to_string(),
+        message: error.to_string(),
+    })
+}
+
+pub(crate) fn write_file(path: &Path, contents: &str) -> Result<(), PhysicsError> {
+    fs::write(path, contents).map_err(|error| PhysicsError::Io {
+        path: path.display().to_string(),
+        message: error.to_string(),
+    })
+}
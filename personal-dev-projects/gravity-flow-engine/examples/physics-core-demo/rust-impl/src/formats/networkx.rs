@@ -0,0 +1,233 @@
+// NetworkX node-link JSON, as written by `networkx.node_link_data` (and by
+// `save_network_data` in pattern_network_visualizer.py):
+//
+//   { "directed": false, "multigraph": false, "graph": {},
+//     "nodes": [{ "id": "feedback-loops", "category": "process", ... }],
+//     "links": [{ "source": "feedback-loops", "target": "homeostasis", ... }] }
+//
+// Node and edge attributes are flat. They are mapped onto Node / Link and
+// their physical properties through an AttributeMapping, as in GraphML and
+// GEXF; everything else is kept in `metadata` and written back on export.
+// So are the values the engine can't represent: a numeric node id (as
+// `metadata.id`, the node's id being its text) and a link `type` that isn't
+// a RelationshipType.
+
+use super::attributes::{number, AttributeMapping, Attributes};
+use super::{read_file, write_file, ImportDefaults};
+use crate::error::PhysicsError;
+use crate::graph::Graph;
+use crate::physics_engine::{Link, Node};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+fn error(message: String) -> PhysicsError {
+    PhysicsError::Parse {
+        format: "NetworkX node-link JSON",
+        message,
+    }
+}
+
+// Parses and validates a node-link document. Links of a directed graph are
+// one-way unless they carry a true mapped `bidirectional`.
+pub fn from_str(input: &str, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    let value: Value = serde_json::from_str(input).map_err(|e| error(e.to_string()))?;
+    from_value(&value, mapping, defaults)
+}
+
+pub fn from_value(value: &Value, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    let document = value.as_object().ok_or_else(|| error("expected an object".to_string()))?;
+    let directed = document.get("directed").and_then(Value::as_bool).unwrap_or(false);
+
+    let nodes = array(document, "nodes")?
+        .iter()
+        .enumerate()
+        .map(|(i, node)| read_node(node, mapping, defaults).map_err(|message| error(format!("node {}: {}", i, message))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // NetworkX 3.4 calls the edge list "edges"
+    let edges = match document.get("links") {
+        Some(_) => array(document, "links")?,
+        None => array(document, "edges")?,
+    };
+    let links = edges
+        .iter()
+        .enumerate()
+        .map(|(i, link)| {
+            read_link(link, directed, mapping, defaults).map_err(|message| error(format!("link {}: {}", i, message)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let metadata = match document.get("graph") {
+        Some(Value::Object(attributes)) => attributes.clone().into_iter().collect(),
+        _ => BTreeMap::new(),
+    };
+
+    let graph = Graph { nodes, links, metadata };
+    graph.validate()?;
+    Ok(graph)
+}
+
+fn array<'a>(document: &'a Map<String, Value>, key: &str) -> Result<&'a Vec<Value>, PhysicsError> {
+    match document.get(key) {
+        Some(Value::Array(items)) => Ok(items),
+        Some(_) => Err(error(format!("\"{}\" must be an array", key))),
+        None => Err(error(format!("missing \"{}\"", key))),
+    }
+}
+
+// NetworkX ids can be any hashable; strings and numbers are supported
+fn id(value: Option<Value>, key: &str) -> Result<String, String> {
+    match value {
+        Some(Value::String(id)) => Ok(id),
+        Some(Value::Number(id)) => Ok(id.to_string()),
+        Some(_) => Err(format!("\"{}\" must be a string or a number", key)),
+        None => Err(format!("missing \"{}\"", key)),
+    }
+}
+
+// Pinned coordinate; null leaves the node free
+fn pin(attributes: &mut Map<String, Value>, key: &str) -> Result<Option<f64>, String> {
+    match attributes.remove(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => number(key, &value).map(Some),
+    }
+}
+
+fn read_node(value: &Value, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Node, String> {
+    let mut attributes = value.as_object().ok_or("expected an object")?.clone();
+    let original = attributes.remove("id");
+    let numeric = original.as_ref().filter(|id| id.is_number()).cloned();
+    let id = id(original, "id")?;
+    let (fx, fy) = (pin(&mut attributes, "fx")?, pin(&mut attributes, "fy")?);
+
+    let mut node = mapping.node(id, attributes.into_iter().collect(), defaults)?;
+    if let Some(numeric) = numeric {
+        node.metadata.insert("id".to_string(), numeric);
+    }
+    node.fx = fx;
+    node.fy = fy;
+    Ok(node)
+}
+
+fn read_link(value: &Value, directed: bool, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Link, String> {
+    let mut attributes = value.as_object().ok_or("expected an object")?.clone();
+    let source = id(attributes.remove("source"), "source")?;
+    let target = id(attributes.remove("target"), "target")?;
+    mapping.link(source, target, !directed, attributes.into_iter().collect(), defaults)
+}
+
+// Id of a node as it is written out: the original number for nodes imported
+// with a numeric id, the id string otherwise
+fn original_id(node: &Node) -> Value {
+    match node.metadata.get("id") {
+        Some(original @ Value::Number(number)) if number.to_string() == node.id => original.clone(),
+        _ => Value::from(node.id.as_str()),
+    }
+}
+
+// Node-link document for a graph, with current node positions as the mapped
+// x/y. The graph is directed if any of its links is one-way.
+pub fn to_value(graph: &Graph, mapping: &AttributeMapping) -> Value {
+    let directed = graph.links.iter().any(|link| !link.physical_properties.bidirectional);
+    let ids: HashMap<&str, Value> = graph.nodes.iter().map(|node| (node.id.as_str(), original_id(node))).collect();
+    let endpoint = |id: &str| ids.get(id).cloned().unwrap_or_else(|| Value::from(id));
+
+    let nodes: Vec<Value> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            let mut attributes = object(mapping.node_attributes(node));
+            attributes.insert("id".to_string(), original_id(node));
+            if let Some(fx) = node.fx {
+                attributes.insert("fx".to_string(), Value::from(fx));
+            }
+            if let Some(fy) = node.fy {
+                attributes.insert("fy".to_string(), Value::from(fy));
+            }
+            Value::Object(attributes)
+        })
+        .collect();
+
+    let links: Vec<Value> = graph
+        .links
+        .iter()
+        .map(|link| {
+            let mut attributes = object(mapping.link_attributes(link));
+            attributes.insert("source".to_string(), endpoint(&link.source));
+            attributes.insert("target".to_string(), endpoint(&link.target));
+            Value::Object(attributes)
+        })
+        .collect();
+
+    serde_json::json!({
+        "directed": directed,
+        "multigraph": false,
+        "graph": graph.metadata,
+        "nodes": nodes,
+        "links": links,
+    })
+}
+
+fn object(attributes: Attributes) -> Map<String, Value> {
+    attributes.into_iter().collect()
+}
+
+pub fn read(path: impl AsRef<Path>, mapping: &AttributeMapping, defaults: &ImportDefaults) -> Result<Graph, PhysicsError> {
+    from_str(&read_file(path.as_ref())?, mapping, defaults)
+}
+
+pub fn to_string(graph: &Graph, mapping: &AttributeMapping) -> String {
+    serde_json::to_string_pretty(&to_value(graph, mapping)).expect("node-link documents always serialize")
+}
+
+pub fn write(path: impl AsRef<Path>, graph: &Graph, mapping: &AttributeMapping) -> Result<(), PhysicsError> {
+    write_file(path.as_ref(), &to_string(graph, mapping))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::RelationshipType;
+
+    #[test]
+    fn round_trip_keeps_numeric_ids_and_unknown_types() {
+        let input = serde_json::json!({
+            "directed": true,
+            "multigraph": false,
+            "graph": { "name": "karate" },
+            "nodes": [
+                { "id": 1, "club": "Mr. Hi" },
+                { "id": 2.5, "mass": 2.0 },
+                { "id": "three" },
+            ],
+            "links": [
+                { "source": 1, "target": 2.5, "type": "reinforcing" },
+                { "source": 2.5, "target": "three", "type": 7, "weight": 0.5 },
+                { "source": "three", "target": 1, "type": "causal" },
+            ],
+        });
+        let graph = from_value(&input, &AttributeMapping::default(), &ImportDefaults::default()).unwrap();
+        assert_eq!(graph.nodes[0].id, "1");
+        assert_eq!(graph.links[0].relationship_type, RelationshipType::Other);
+        assert_eq!(graph.links[2].relationship_type, RelationshipType::Causal);
+
+        let output = to_value(&graph, &AttributeMapping::default());
+        for (i, id) in [Value::from(1), Value::from(2.5), Value::from("three")].iter().enumerate() {
+            assert_eq!(&output["nodes"][i]["id"], id);
+        }
+        assert_eq!(output["nodes"][0]["club"], "Mr. Hi");
+        assert_eq!(output["links"][0]["source"], 1);
+        assert_eq!(output["links"][0]["target"], 2.5);
+        assert_eq!(output["links"][0]["type"], "reinforcing");
+        assert_eq!(output["links"][1]["type"], 7);
+        assert_eq!(output["links"][1]["weight"], 0.5);
+        assert_eq!(output["links"][2]["type"], "causal");
+        assert_eq!(output["links"][2]["bidirectional"], false);
+
+        // A second round trip changes nothing
+        let mapping = AttributeMapping::default();
+        let again = to_value(&from_value(&output, &mapping, &ImportDefaults::default()).unwrap(), &mapping);
+        assert_eq!(again, output);
+    }
+}
@@ -0,0 +1,164 @@
+// Graph validation and resolution of link endpoints to node indices
+
+use crate::error::PhysicsError;
+use crate::physics_engine::{Link, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// Nodes and links as read from or written to a file
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    #[serde(default, alias = "edges")]
+    pub links: Vec<Link>,
+    // Attributes of the graph as a whole
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+}
+
+impl Graph {
+    // Same checks as PhysicsEngine::new
+    pub fn validate(&self) -> Result<(), PhysicsError> {
+        resolve(&self.nodes, &self.links).map(|_| ())
+    }
+}
+
+// Node id -> position in the node list
+pub type NodeIndex = HashMap<String, usize>;
+
+// Maps node ids to their position in the node list, rejecting duplicate ids
+pub fn node_index(nodes: &[Node]) -> Result<NodeIndex, PhysicsError> {
+    let mut index = HashMap::with_capacity(nodes.len());
+    for (i, node) in nodes.iter().enumerate() {
+        if index.insert(node.id.clone(), i).is_some() {
+            return Err(PhysicsError::DuplicateNode(node.id.clone()));
+        }
+    }
+    Ok(index)
+}
+
+// Resolves every link to (source index, target index), rejecting links to
+// unknown nodes and links from a node to itself
+pub fn resolve_links(index: &NodeIndex, links: &[Link]) -> Result<Vec<(usize, usize)>, PhysicsError> {
+    links
+        .iter()
+        .enumerate()
+        .map(|(i, link)| {
+            let endpoint = |id: &str| {
+                index.get(id).copied().ok_or_else(|| PhysicsError::DanglingLink {
+                    link: i,
+                    node: id.to_string(),
+                })
+            };
+            let source = endpoint(&link.source)?;
+            let target = endpoint(&link.target)?;
+            if source == target {
+                return Err(PhysicsError::SelfLoop {
+                    link: i,
+                    node: link.source.clone(),
+                });
+            }
+            Ok((source, target))
+        })
+        .collect()
+}
+
+// Validates the graph and resolves it in one go
+pub fn resolve(nodes: &[Node], links: &[Link]) -> Result<(NodeIndex, Vec<(usize, usize)>), PhysicsError> {
+    let index = node_index(nodes)?;
+    let endpoints = resolve_links(&index, links)?;
+    Ok((index, endpoints))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::{LinkPhysicalProperties, PhysicalProperties, PhysicsEngine, RelationshipType, SpringModel};
+
+    fn node(id: &str) -> Node {
+        Node {
+            id: id.to_string(),
+            label: String::new(),
+            category: String::new(),
+            physical_properties: PhysicalProperties {
+                mass: 1.0,
+                charge: 0.0,
+                friction: 0.0,
+                radius: 1.0,
+                fixed: false,
+                initial_position: None,
+            },
+            metadata: BTreeMap::new(),
+            x: 0.0,
+            y: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            fx: None,
+            fy: None,
+        }
+    }
+
+    fn link(source: &str, target: &str) -> Link {
+        Link {
+            source: source.to_string(),
+            target: target.to_string(),
+            relationship_type: RelationshipType::Related,
+            physical_properties: LinkPhysicalProperties {
+                stiffness: 0.1,
+                length: 50.0,
+                elasticity: 0.0,
+                spring_model: SpringModel::Linear,
+                bidirectional: true,
+            },
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    fn nodes(ids: &[&str]) -> Vec<Node> {
+        ids.iter().map(|id| node(id)).collect()
+    }
+
+    #[test]
+    fn resolve_maps_links_to_node_indices() {
+        let (index, endpoints) = resolve(&nodes(&["a", "b", "c"]), &[link("c", "a"), link("b", "c")]).unwrap();
+        assert_eq!(index["b"], 1);
+        assert_eq!(endpoints, [(2, 0), (1, 2)]);
+    }
+
+    #[test]
+    fn invalid_graphs_are_rejected_with_the_offending_id() {
+        let cases = [
+            (nodes(&["a", "b", "a"]), vec![], PhysicsError::DuplicateNode("a".to_string())),
+            (
+                nodes(&["a", "b"]),
+                vec![link("a", "b"), link("b", "c")],
+                PhysicsError::DanglingLink { link: 1, node: "c".to_string() },
+            ),
+            (
+                nodes(&["a", "b"]),
+                vec![link("missing", "b")],
+                PhysicsError::DanglingLink { link: 0, node: "missing".to_string() },
+            ),
+            (
+                nodes(&["a", "b"]),
+                vec![link("a", "b"), link("b", "b")],
+                PhysicsError::SelfLoop { link: 1, node: "b".to_string() },
+            ),
+        ];
+
+        for (nodes, links, error) in cases {
+            let graph = Graph { nodes: nodes.clone(), links: links.clone(), ..Default::default() };
+            assert_eq!(graph.validate(), Err(error.clone()));
+            assert_eq!(PhysicsEngine::new(nodes, links, None).err(), Some(error));
+        }
+    }
+
+    #[test]
+    fn set_graph_keeps_the_old_graph_when_the_new_one_is_invalid() {
+        let mut engine = PhysicsEngine::new(nodes(&["a", "b"]), vec![link("a", "b")], None).unwrap();
+        let error = engine.set_graph(nodes(&["a"]), vec![link("a", "b")]).unwrap_err();
+        assert_eq!(error, PhysicsError::DanglingLink { link: 0, node: "b".to_string() });
+        assert_eq!(engine.nodes.len(), 2);
+        assert_eq!(engine.find_node_index("b"), Some(1));
+    }
+}
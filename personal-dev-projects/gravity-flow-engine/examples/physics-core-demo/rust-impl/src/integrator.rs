@@ -0,0 +1,323 @@
+// Time integration schemes for advancing node positions and velocities
+
+use crate::physics_engine::Node;
+
+// Evaluates all forces for the given node state and returns the acceleration
+// of every node. Positions and velocities are left as they were.
+pub type Accelerations<'a> = dyn FnMut(&mut [Node]) -> Vec<(f64, f64)> + 'a;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Step {
+    pub dt: f64,
+    // Fraction of velocity kept per unit of time, before per-node friction
+    pub velocity_decay: f64,
+}
+
+impl Step {
+    // Velocity kept by a node over one step
+    pub fn damping(&self, node: &Node) -> f64 {
+        let friction = node.physical_properties.friction.clamp(0.0, 1.0);
+        (self.velocity_decay * (1.0 - friction)).powf(self.dt)
+    }
+}
+
+pub trait Integrator {
+    fn step(&mut self, nodes: &mut [Node], step: &Step, accelerations: &mut Accelerations);
+}
+
+// d3-style update: forces add to velocity, velocity decays, position follows.
+// With dt = 1 this is d3.forceSimulation's tick, given d3's settings:
+// `velocity_decay` here is the fraction kept (d3's velocityDecay of 0.4 is
+// 0.6 here), node friction damps on top of it, and mass-scaled forces see
+// unit masses only when every mass is 1.
+#[derive(Clone, Debug, Default)]
+pub struct D3Integrator;
+
+impl Integrator for D3Integrator {
+    fn step(&mut self, nodes: &mut [Node], step: &Step, accelerations: &mut Accelerations) {
+        let acceleration = accelerations(nodes);
+        for (node, (ax, ay)) in nodes.iter_mut().zip(acceleration) {
+            let damping = step.damping(node);
+            node.vx = (node.vx + ax * step.dt) * damping;
+            node.vy = (node.vy + ay * step.dt) * damping;
+            node.x += node.vx * step.dt;
+            node.y += node.vy * step.dt;
+        }
+    }
+}
+
+// Symplectic Euler: kick with the current force, drift with the new velocity
+#[derive(Clone, Debug, Default)]
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn step(&mut self, nodes: &mut [Node], step: &Step, accelerations: &mut Accelerations) {
+        let acceleration = accelerations(nodes);
+        for (node, (ax, ay)) in nodes.iter_mut().zip(acceleration) {
+            node.vx += ax * step.dt;
+            node.vy += ay * step.dt;
+            node.x += node.vx * step.dt;
+            node.y += node.vy * step.dt;
+            apply_damping(node, step);
+        }
+    }
+}
+
+// Velocity Verlet. The start acceleration is evaluated afresh every step,
+// at two force evaluations per step: reusing the previous step's end
+// acceleration would carry over the previous alpha, and miss everything that
+// moved nodes after integration (position adjustments, constraints, walls)
+// or replaced the graph.
+#[derive(Clone, Debug, Default)]
+pub struct VelocityVerlet;
+
+impl Integrator for VelocityVerlet {
+    fn step(&mut self, nodes: &mut [Node], step: &Step, accelerations: &mut Accelerations) {
+        let dt = step.dt;
+        let start = accelerations(nodes);
+
+        for (node, &(ax, ay)) in nodes.iter_mut().zip(&start) {
+            node.x += node.vx * dt + 0.5 * ax * dt * dt;
+            node.y += node.vy * dt + 0.5 * ay * dt * dt;
+        }
+
+        let end = accelerations(nodes);
+        for ((node, &(ax0, ay0)), &(ax1, ay1)) in nodes.iter_mut().zip(&start).zip(&end) {
+            node.vx += 0.5 * (ax0 + ax1) * dt;
+            node.vy += 0.5 * (ay0 + ay1) * dt;
+            apply_damping(node, step);
+        }
+    }
+}
+
+// Classic fourth-order Runge-Kutta over (position, velocity)
+#[derive(Clone, Debug, Default)]
+pub struct RungeKutta4;
+
+impl Integrator for RungeKutta4 {
+    fn step(&mut self, nodes: &mut [Node], step: &Step, accelerations: &mut Accelerations) {
+        let dt = step.dt;
+        let initial: Vec<[f64; 4]> = nodes.iter().map(|node| [node.x, node.y, node.vx, node.vy]).collect();
+
+        // Each stage is a derivative (dx, dy, dvx, dvy) per node
+        let mut stages: Vec<Vec<[f64; 4]>> = Vec::with_capacity(4);
+        for (stage, scale) in [0.0, 0.5, 0.5, 1.0].into_iter().enumerate() {
+            // Move the nodes to the state this stage is evaluated at
+            for (i, node) in nodes.iter_mut().enumerate() {
+                let offset = if stage == 0 { [0.0; 4] } else { stages[stage - 1][i] };
+                node.x = initial[i][0] + offset[0] * dt * scale;
+                node.y = initial[i][1] + offset[1] * dt * scale;
+                node.vx = initial[i][2] + offset[2] * dt * scale;
+                node.vy = initial[i][3] + offset[3] * dt * scale;
+            }
+
+            let acceleration = accelerations(nodes);
+            stages.push(
+                nodes.iter()
+                    .zip(acceleration)
+                    .map(|(node, (ax, ay))| [node.vx, node.vy, ax, ay])
+                    .collect(),
+            );
+        }
+
+        for (i, node) in nodes.iter_mut().enumerate() {
+            let slope = |k: usize| {
+                (stages[0][i][k] + 2.0 * stages[1][i][k] + 2.0 * stages[2][i][k] + stages[3][i][k]) / 6.0
+            };
+            node.x = initial[i][0] + slope(0) * dt;
+            node.y = initial[i][1] + slope(1) * dt;
+            node.vx = initial[i][2] + slope(2) * dt;
+            node.vy = initial[i][3] + slope(3) * dt;
+            apply_damping(node, step);
+        }
+    }
+}
+
+fn apply_damping(node: &mut Node, step: &Step) {
+    let damping = step.damping(node);
+    node.vx *= damping;
+    node.vy *= damping;
+}
+
+// Step-size controller that keeps stiff or highly charged networks stable:
+// velocities and per-tick displacement are capped, and dt shrinks when the
+// kinetic energy spikes and grows back while the system is calm
+#[derive(Clone, Debug)]
+pub struct AdaptiveStep {
+    // Largest speed a node may have; a node moves at most max_velocity * dt per tick
+    pub max_velocity: f64,
+    pub min_dt: f64,
+    pub max_dt: f64,
+    // Kinetic energy growth between ticks that counts as a spike
+    pub spike_ratio: f64,
+    // Factor applied to dt after a spike
+    pub shrink: f64,
+    // Factor applied to dt while kinetic energy is not rising
+    pub grow: f64,
+}
+
+impl Default for AdaptiveStep {
+    fn default() -> Self {
+        AdaptiveStep {
+            max_velocity: 50.0,
+            min_dt: 0.05,
+            max_dt: 1.0,
+            spike_ratio: 2.0,
+            shrink: 0.5,
+            grow: 1.1,
+        }
+    }
+}
+
+impl AdaptiveStep {
+    // Clamps velocities and pulls back nodes that moved too far this tick
+    pub fn limit(&self, nodes: &mut [Node], previous_positions: &[(f64, f64)], dt: f64) {
+        let max_displacement = self.max_velocity * dt;
+
+        for (node, &(x, y)) in nodes.iter_mut().zip(previous_positions) {
+            let speed = (node.vx * node.vx + node.vy * node.vy).sqrt();
+            if speed > self.max_velocity {
+                node.vx *= self.max_velocity / speed;
+                node.vy *= self.max_velocity / speed;
+            }
+
+            let (dx, dy) = (node.x - x, node.y - y);
+            let displacement = (dx * dx + dy * dy).sqrt();
+            if displacement > max_displacement {
+                let scale = max_displacement / displacement;
+                node.x = x + dx * scale;
+                node.y = y + dy * scale;
+            }
+        }
+    }
+
+    // Time step for the next tick given the kinetic energy before this tick
+    // (None on the first tick of a graph) and after it
+    pub fn next_dt(&self, dt: f64, previous_energy: Option<f64>, energy: f64) -> f64 {
+        let dt = match previous_energy {
+            Some(previous) if energy <= previous => dt * self.grow,
+            // Any motion at all is an infinite rise over a graph at rest, so
+            // there is nothing to compare against
+            Some(previous) if previous > f64::EPSILON && energy > previous * self.spike_ratio => dt * self.shrink,
+            _ => dt,
+        };
+        dt.clamp(self.min_dt, self.max_dt)
+    }
+}
+
+// Total kinetic energy, ½·m·v² summed over all nodes
+pub fn kinetic_energy(nodes: &[Node]) -> f64 {
+    nodes
+        .iter()
+        .map(|node| {
+            let mass = 1.0 / node.physical_properties.inverse_mass();
+            0.5 * mass * (node.vx * node.vx + node.vy * node.vy)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics_engine::PhysicalProperties;
+    use std::collections::BTreeMap;
+    use std::f64::consts::PI;
+
+    // Undamped unit mass on a unit spring, started at x = 1 at rest
+    fn oscillator() -> Vec<Node> {
+        vec![Node {
+            id: "a".to_string(),
+            label: String::new(),
+            category: String::new(),
+            physical_properties: PhysicalProperties {
+                mass: 1.0,
+                charge: 0.0,
+                friction: 0.0,
+                radius: 1.0,
+                fixed: false,
+                initial_position: None,
+            },
+            metadata: BTreeMap::new(),
+            x: 1.0,
+            y: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            fx: None,
+            fy: None,
+        }]
+    }
+
+    fn energy(node: &Node) -> f64 {
+        0.5 * (node.vx * node.vx + node.x * node.x)
+    }
+
+    // Runs the oscillator for `steps` steps of `dt` and returns the final
+    // state and the largest relative energy error seen
+    fn run(integrator: &mut dyn Integrator, dt: f64, steps: usize) -> (Node, f64) {
+        let mut nodes = oscillator();
+        let initial = energy(&nodes[0]);
+        let step = Step { dt, velocity_decay: 1.0 };
+        let mut spring = |nodes: &mut [Node]| nodes.iter().map(|node| (-node.x, -node.y)).collect();
+
+        let mut drift: f64 = 0.0;
+        for _ in 0..steps {
+            integrator.step(&mut nodes, &step, &mut spring);
+            drift = drift.max((energy(&nodes[0]) - initial).abs() / initial);
+        }
+        (nodes.remove(0), drift)
+    }
+
+    // Distance in phase space from the exact state (cos t, -sin t) at t = 1
+    fn error_at_one(integrator: &mut dyn Integrator, steps: usize) -> f64 {
+        let (node, _) = run(integrator, 1.0 / steps as f64, steps);
+        ((node.x - 1f64.cos()).powi(2) + (node.vx + 1f64.sin()).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn energy_stays_bounded_over_many_periods() {
+        // 1000 periods at 64 steps each
+        let dt = 2.0 * PI / 64.0;
+        let steps = 64_000;
+        let (_, euler) = run(&mut SemiImplicitEuler, dt, steps);
+        let (_, verlet) = run(&mut VelocityVerlet, dt, steps);
+        let (_, rk4) = run(&mut RungeKutta4, dt, steps);
+        assert!(euler < 0.06, "semi-implicit Euler energy error {}", euler);
+        assert!(verlet < 0.003, "Verlet energy error {}", verlet);
+        // RK4 isn't symplectic: its energy error is far smaller per step but
+        // slowly accumulates instead of oscillating
+        assert!(rk4 < 1e-3, "RK4 energy error {}", rk4);
+
+        // The symplectic schemes' error doesn't grow with the run length
+        let (_, euler_short) = run(&mut SemiImplicitEuler, dt, steps / 10);
+        let (_, verlet_short) = run(&mut VelocityVerlet, dt, steps / 10);
+        assert!(euler < euler_short * 1.01 && verlet < verlet_short * 1.01);
+    }
+
+    #[test]
+    fn halving_the_step_shows_each_scheme_order() {
+        let cases: [(&mut dyn Integrator, f64); 3] = [
+            (&mut SemiImplicitEuler, 2.0),
+            (&mut VelocityVerlet, 4.0),
+            (&mut RungeKutta4, 16.0),
+        ];
+        for (integrator, expected) in cases {
+            let ratio = error_at_one(integrator, 16) / error_at_one(integrator, 32);
+            assert!((ratio / expected - 1.0).abs() < 0.1, "error ratio {} instead of {}", ratio, expected);
+        }
+    }
+
+    #[test]
+    fn first_tick_keeps_the_time_step() {
+        let adaptive = AdaptiveStep::default();
+        assert_eq!(adaptive.next_dt(1.0, None, 500.0), 1.0);
+        assert_eq!(adaptive.next_dt(1.0, Some(0.0), 500.0), 1.0);
+    }
+
+    #[test]
+    fn energy_spikes_shrink_the_time_step() {
+        let adaptive = AdaptiveStep::default();
+        assert_eq!(adaptive.next_dt(1.0, Some(100.0), 500.0), 0.5);
+        assert_eq!(adaptive.next_dt(1.0, Some(100.0), 150.0), 1.0);
+        assert_eq!(adaptive.next_dt(0.5, Some(100.0), 80.0), 0.55);
+    }
+}
@@ -0,0 +1,387 @@
+// Initial placement strategies for nodes without an explicit initial position
+
+use crate::physics_engine::Node;
+use rand::{Rng, RngCore};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+// Fraction of the canvas kept free around layouts that fill it
+const MARGIN: f64 = 0.1;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum InitialLayout {
+    // Uniformly random over the canvas
+    #[default]
+    Random,
+    // Sunflower spiral around the canvas center, as d3 does
+    Phyllotaxis { radius: f64 },
+    // Evenly spaced on a circle
+    Circular,
+    // Row-major grid over the canvas
+    Grid,
+    // One angular sector per category, nodes spread within their sector
+    CategorySectors,
+    // Coordinates from the two smallest non-trivial Laplacian eigenvectors
+    Spectral,
+}
+
+impl InitialLayout {
+    // d3's default initial radius for phyllotaxis placement
+    pub fn phyllotaxis() -> Self {
+        InitialLayout::Phyllotaxis { radius: 10.0 }
+    }
+}
+
+// Places the `unplaced` nodes; `edges` are the links resolved to node indices
+pub fn place_nodes(
+    layout: &InitialLayout,
+    nodes: &mut [Node],
+    unplaced: &[usize],
+    edges: &[(usize, usize)],
+    width: f64,
+    height: f64,
+    rng: &mut dyn RngCore,
+) {
+    if unplaced.is_empty() {
+        return;
+    }
+
+    let positions = match layout {
+        InitialLayout::Random => unplaced
+            .iter()
+            .map(|_| (rng.gen::<f64>() * width, rng.gen::<f64>() * height))
+            .collect(),
+        InitialLayout::Phyllotaxis { radius } => phyllotaxis(unplaced.len(), *radius, width, height),
+        InitialLayout::Circular => circular(unplaced.len(), width, height),
+        InitialLayout::Grid => grid(unplaced.len(), width, height),
+        InitialLayout::CategorySectors => {
+            let categories: Vec<&str> = unplaced.iter().map(|&i| nodes[i].category.as_str()).collect();
+            category_sectors(&categories, width, height)
+        }
+        InitialLayout::Spectral => {
+            let coordinates = spectral(nodes.len(), edges, rng);
+            match coordinates {
+                Some(coordinates) => {
+                    let selected: Vec<(f64, f64)> = unplaced.iter().map(|&i| coordinates[i]).collect();
+                    fit_to_canvas(&selected, width, height)
+                }
+                None => circular(unplaced.len(), width, height),
+            }
+        }
+    };
+
+    for (&i, (x, y)) in unplaced.iter().zip(positions) {
+        nodes[i].x = x;
+        nodes[i].y = y;
+    }
+}
+
+fn phyllotaxis(count: usize, radius: f64, width: f64, height: f64) -> Vec<(f64, f64)> {
+    let angle = PI * (3.0 - 5.0_f64.sqrt());
+    (0..count)
+        .map(|i| {
+            let r = radius * (0.5 + i as f64).sqrt();
+            let a = i as f64 * angle;
+            (width / 2.0 + r * a.cos(), height / 2.0 + r * a.sin())
+        })
+        .collect()
+}
+
+pub(crate) fn circular(count: usize, width: f64, height: f64) -> Vec<(f64, f64)> {
+    let radius = width.min(height) * (0.5 - MARGIN);
+    (0..count)
+        .map(|i| {
+            let a = 2.0 * PI * i as f64 / count as f64;
+            (width / 2.0 + radius * a.cos(), height / 2.0 + radius * a.sin())
+        })
+        .collect()
+}
+
+pub(crate) fn grid(count: usize, width: f64, height: f64) -> Vec<(f64, f64)> {
+    let columns = (count as f64).sqrt().ceil() as usize;
+    let rows = count.div_ceil(columns);
+    let (x0, y0) = (width * MARGIN, height * MARGIN);
+    let cell_width = width * (1.0 - 2.0 * MARGIN) / columns as f64;
+    let cell_height = height * (1.0 - 2.0 * MARGIN) / rows as f64;
+    (0..count)
+        .map(|i| {
+            let (row, column) = (i / columns, i % columns);
+            (
+                x0 + (column as f64 + 0.5) * cell_width,
+                y0 + (row as f64 + 0.5) * cell_height,
+            )
+        })
+        .collect()
+}
+
+fn category_sectors(categories: &[&str], width: f64, height: f64) -> Vec<(f64, f64)> {
+    // Sectors follow the order in which categories first appear
+    let mut order: Vec<&str> = Vec::new();
+    let mut members: HashMap<&str, usize> = HashMap::new();
+    for &category in categories {
+        if !members.contains_key(category) {
+            order.push(category);
+        }
+        *members.entry(category).or_insert(0) += 1;
+    }
+
+    let sector = 2.0 * PI / order.len() as f64;
+    let max_radius = width.min(height) * (0.5 - MARGIN);
+    let mut placed: HashMap<&str, usize> = HashMap::new();
+
+    categories
+        .iter()
+        .map(|&category| {
+            let sector_index = order.iter().position(|&c| c == category).unwrap_or(0);
+            let count = members[category];
+            let k = placed.entry(category).or_insert(0);
+            let rank = *k;
+            *k += 1;
+
+            // Spread members over the middle of the sector, alternating rings
+            let fraction = (rank as f64 + 0.5) / count as f64;
+            let a = sector * (sector_index as f64 + 0.1 + 0.8 * fraction);
+            let r = max_radius * if rank.is_multiple_of(2) { 0.6 } else { 0.9 };
+            (width / 2.0 + r * a.cos(), height / 2.0 + r * a.sin())
+        })
+        .collect()
+}
+
+// Spectral coordinates for every node. Each connected component is laid out
+// on its own, since the eigenvectors of a disconnected graph are constant
+// over its components, and the components are then packed side by side.
+fn spectral(n: usize, edges: &[(usize, usize)], rng: &mut dyn RngCore) -> Option<Vec<(f64, f64)>> {
+    if n < 3 || edges.is_empty() {
+        return None;
+    }
+
+    let mut components = components(n, edges);
+    // Largest first, so the packing puts them in the top rows
+    components.sort_by_key(|members| std::cmp::Reverse(members.len()));
+
+    let mut coordinates = vec![(0.0, 0.0); n];
+    let mut local = vec![0; n];
+    let total: f64 = components.iter().map(|members| members.len() as f64).sum();
+    let row_width = total.sqrt();
+    let (mut x, mut y, mut row_height) = (0.0, 0.0, 0.0);
+    for members in &components {
+        for (k, &i) in members.iter().enumerate() {
+            local[i] = k;
+        }
+        let component_edges: Vec<(usize, usize)> = edges
+            .iter()
+            .filter(|&&(s, _)| members.binary_search(&s).is_ok())
+            .map(|&(s, t)| (local[s], local[t]))
+            .collect();
+        let mut points = match members.len() {
+            1 => vec![(0.0, 0.0)],
+            2 => vec![(-1.0, 0.0), (1.0, 0.0)],
+            m => fiedler_plane(m, &component_edges, rng).unwrap_or_else(|| circular(m, 2.0, 2.0)),
+        };
+        separate(&mut points);
+
+        // Each component gets a square whose area grows with its size
+        let side = (members.len() as f64).sqrt();
+        if x > 0.0 && x + side > row_width {
+            (x, y) = (0.0, y + row_height);
+            row_height = 0.0;
+        }
+        let (min_x, max_x) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+        let (min_y, max_y) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+        let scale = side * (1.0 - 2.0 * MARGIN) / (max_x - min_x).max(max_y - min_y).max(1e-12);
+        for (&i, &(px, py)) in members.iter().zip(&points) {
+            coordinates[i] = (
+                x + side / 2.0 + (px - (min_x + max_x) / 2.0) * scale,
+                y + side / 2.0 + (py - (min_y + max_y) / 2.0) * scale,
+            );
+        }
+        x += side;
+        row_height = row_height.max(side);
+    }
+
+    Some(coordinates)
+}
+
+// Connected components, each as a sorted list of node indices
+fn components(n: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut neighbors = vec![Vec::new(); n];
+    for &(s, t) in edges {
+        neighbors[s].push(t);
+        neighbors[t].push(s);
+    }
+
+    let mut seen = vec![false; n];
+    let mut components = Vec::new();
+    for start in 0..n {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut members = vec![start];
+        let mut next = 0;
+        while next < members.len() {
+            for &j in &neighbors[members[next]] {
+                if !seen[j] {
+                    seen[j] = true;
+                    members.push(j);
+                }
+            }
+            next += 1;
+        }
+        members.sort_unstable();
+        components.push(members);
+    }
+    components
+}
+
+// Power iteration on (c·I − L), deflating the constant vector, yields the
+// eigenvectors of the Laplacian L of a connected graph with the smallest
+// non-zero eigenvalues
+fn fiedler_plane(n: usize, edges: &[(usize, usize)], rng: &mut dyn RngCore) -> Option<Vec<(f64, f64)>> {
+    let mut degree = vec![0.0; n];
+    for &(s, t) in edges {
+        degree[s] += 1.0;
+        degree[t] += 1.0;
+    }
+    // Gershgorin bound: every Laplacian eigenvalue is at most 2·max degree
+    let shift = 2.0 * degree.iter().cloned().fold(0.0, f64::max) + 1.0;
+
+    let multiply = |v: &[f64]| -> Vec<f64> {
+        let mut out: Vec<f64> = (0..n).map(|i| (shift - degree[i]) * v[i]).collect();
+        for &(s, t) in edges {
+            out[s] += v[t];
+            out[t] += v[s];
+        }
+        out
+    };
+
+    let mut vectors: Vec<Vec<f64>> = Vec::with_capacity(2);
+    for _ in 0..2 {
+        let mut v: Vec<f64> = (0..n).map(|_| rng.gen::<f64>() - 0.5).collect();
+        for _ in 0..500 {
+            v = multiply(&v);
+            orthonormalize(&mut v, &vectors)?;
+        }
+        vectors.push(v);
+    }
+
+    Some((0..n).map(|i| (vectors[0][i], vectors[1][i])).collect())
+}
+
+// Removes the constant component and the given vectors, then normalizes
+fn orthonormalize(v: &mut [f64], basis: &[Vec<f64>]) -> Option<()> {
+    let mean = v.iter().sum::<f64>() / v.len() as f64;
+    v.iter_mut().for_each(|x| *x -= mean);
+    for b in basis {
+        let dot: f64 = v.iter().zip(b).map(|(x, y)| x * y).sum();
+        v.iter_mut().zip(b).for_each(|(x, y)| *x -= dot * y);
+    }
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm < 1e-12 {
+        return None;
+    }
+    v.iter_mut().for_each(|x| *x /= norm);
+    Some(())
+}
+
+// Nodes that are indistinguishable to the eigenvectors (two leaves of the
+// same node, the two sides of a symmetric graph) land on the same point.
+// Points closer than 0.1% of the layout's extent are spread on a small ring
+// around where they met.
+fn separate(points: &mut [(f64, f64)]) {
+    let (min_x, max_x) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+    let (min_y, max_y) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    let span = (max_x - min_x).max(max_y - min_y);
+    if span < 1e-12 {
+        let circle = circular(points.len(), 2.0, 2.0);
+        points.copy_from_slice(&circle);
+        return;
+    }
+    let tolerance = span * 1e-3;
+
+    // Sweep along x, chaining each point to an earlier one it coincides with
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| points[a].0.total_cmp(&points[b].0));
+    let mut group: Vec<usize> = (0..points.len()).collect();
+    for (k, &i) in order.iter().enumerate() {
+        for &j in order[k + 1..].iter().take_while(|&&j| points[j].0 - points[i].0 <= tolerance) {
+            if (points[j].1 - points[i].1).abs() <= tolerance && group[j] == j {
+                group[j] = group[i];
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, &g) in group.iter().enumerate() {
+        members.entry(g).or_default().push(i);
+    }
+    let radius = span * 0.02;
+    for group in members.values().filter(|group| group.len() > 1) {
+        let (cx, cy) = points[group[0]];
+        for (k, &i) in group.iter().enumerate() {
+            let a = 2.0 * PI * k as f64 / group.len() as f64;
+            points[i] = (cx + radius * a.cos(), cy + radius * a.sin());
+        }
+    }
+}
+
+// Scales coordinates uniformly into the canvas, keeping the margin free
+fn fit_to_canvas(points: &[(f64, f64)], width: f64, height: f64) -> Vec<(f64, f64)> {
+    let (min_x, max_x) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+    let (min_y, max_y) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    let span = (max_x - min_x).max(max_y - min_y).max(1e-12);
+    let scale = width.min(height) * (1.0 - 2.0 * MARGIN) / span;
+    let (mid_x, mid_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    points
+        .iter()
+        .map(|&(x, y)| (width / 2.0 + (x - mid_x) * scale, height / 2.0 + (y - mid_y) * scale))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Smallest distance between two points, relative to the layout's extent
+    fn closest_pair(points: &[(f64, f64)]) -> f64 {
+        let mut closest = f64::INFINITY;
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i + 1..] {
+                closest = closest.min((a.0 - b.0).hypot(a.1 - b.1));
+            }
+        }
+        let (min_x, max_x) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+        let (min_y, max_y) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+        closest / (max_x - min_x).max(max_y - min_y)
+    }
+
+    #[test]
+    fn spectral_separates_components() {
+        // Three chains of three nodes
+        let edges = [(0, 1), (1, 2), (3, 4), (4, 5), (6, 7), (7, 8)];
+        let points = spectral(9, &edges, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(closest_pair(&points) > 0.01, "{:?}", points);
+    }
+
+    #[test]
+    fn spectral_separates_isolated_nodes_and_pairs() {
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 4)];
+        let points = spectral(7, &edges, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(closest_pair(&points) > 0.01, "{:?}", points);
+    }
+
+    #[test]
+    fn spectral_separates_symmetric_nodes() {
+        // Leaves of a star, and the two-node side of a complete bipartite
+        // graph, share their eigenvector entries
+        let star = [(0, 1), (0, 2), (0, 3), (0, 4), (0, 5)];
+        let points = spectral(6, &star, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(closest_pair(&points) > 0.001, "{:?}", points);
+
+        let bipartite = [(0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4)];
+        let points = spectral(5, &bipartite, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(closest_pair(&points) > 0.001, "{:?}", points);
+    }
+}
@@ -0,0 +1,11 @@
+pub mod boundary;
+pub mod constraints;
+pub mod error;
+pub mod formats;
+pub mod forces;
+pub mod graph;
+pub mod integrator;
+pub mod layout;
+pub mod physics_engine;
+pub mod quadtree;
+pub mod spatial_grid;
@@ -1,21 +1,145 @@
-mod physics_engine;
-
-use physics_engine::*;
-use std::collections::HashMap;
+use gravity_flow_physics::boundary::Boundary;
+use gravity_flow_physics::error::PhysicsError;
+use gravity_flow_physics::formats::{dot, gexf, graphml, json, markdown, networkx, AttributeMapping, ImportDefaults};
+use gravity_flow_physics::graph::Graph;
+use gravity_flow_physics::physics_engine::*;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::error::Error;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+// Test pattern network used when no input file is given
+const DEFAULT_GRAPH: &str = include_str!("../data/pattern-network.json");
+
+const USAGE: &str = "Usage: gravity-flow-physics [--input graph.json | --patterns dir] [--format json|networkx|graphml|gexf|dot] \
+                     [--output results.json] [--write-layout graph.json]";
+
+// Graph file formats the binary reads and writes
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Format {
+    #[default]
+    Json,
+    NetworkX,
+    GraphMl,
+    Gexf,
+    Dot,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "json" => Ok(Format::Json),
+            "networkx" => Ok(Format::NetworkX),
+            "graphml" => Ok(Format::GraphMl),
+            "gexf" => Ok(Format::Gexf),
+            "dot" => Ok(Format::Dot),
+            _ => Err(format!("unknown format \"{}\"\n{}", name, USAGE)),
+        }
+    }
+
+    fn read(self, path: &Path) -> Result<Graph, PhysicsError> {
+        match self {
+            Format::Json => json::read(path),
+            Format::NetworkX => networkx::read(path, &AttributeMapping::default(), &ImportDefaults::default()),
+            Format::GraphMl => graphml::read(path, &AttributeMapping::default(), &ImportDefaults::default()),
+            Format::Gexf => gexf::read(path, &AttributeMapping::default(), &ImportDefaults::default()),
+            Format::Dot => dot::read(path, &AttributeMapping::default(), &ImportDefaults::default()),
+        }
+    }
+
+    fn write(self, path: &Path, graph: &Graph) -> Result<(), PhysicsError> {
+        match self {
+            Format::Json => json::write(path, graph),
+            Format::NetworkX => networkx::write(path, graph, &AttributeMapping::default()),
+            Format::GraphMl => graphml::write(path, graph, &AttributeMapping::default()),
+            Format::Gexf => gexf::write(path, graph, &AttributeMapping::default()),
+            Format::Dot => dot::write(path, graph, &AttributeMapping::default()),
+        }
+    }
+}
+
+// Command-line arguments
+#[derive(Debug, Default)]
+struct Args {
+    input: Option<PathBuf>,
+    // Directory of Markdown pattern files, read instead of `input`
+    patterns: Option<PathBuf>,
+    // Format of `input` and `write_layout`
+    format: Format,
+    output: Option<PathBuf>,
+    // Where to write the final graph with its node positions
+    write_layout: Option<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        let slot = match arg.as_str() {
+            "--format" | "-f" => {
+                let name = args.next().ok_or_else(|| format!("{} needs a format name\n{}", arg, USAGE))?;
+                parsed.format = Format::parse(&name)?;
+                continue;
+            }
+            "--input" | "-i" => &mut parsed.input,
+            "--patterns" | "-p" => &mut parsed.patterns,
+            "--output" | "-o" => &mut parsed.output,
+            "--write-layout" | "-w" => &mut parsed.write_layout,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("unknown argument \"{}\"\n{}", arg, USAGE)),
+        };
+        let value = args.next().ok_or_else(|| format!("{} needs a file path\n{}", arg, USAGE))?;
+        *slot = Some(PathBuf::from(value));
+    }
+    if parsed.input.is_some() && parsed.patterns.is_some() {
+        return Err(format!("--input and --patterns can't be combined\n{}", USAGE));
+    }
+    Ok(parsed)
+}
 
-fn main() {
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = parse_args(env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(2);
+    });
+    
     println!("\n=== Gravity Flow Engine Physics Test (Rust) ===\n");
     
-    // Create the test pattern data
-    let pattern_data = create_test_pattern_data();
+    // Load the graph, falling back to the test pattern network
+    let graph = match (&args.input, &args.patterns) {
+        (Some(path), _) => args.format.read(path)?,
+        (None, Some(directory)) => markdown::read_dir(directory, &markdown::PatternImport::default())?,
+        (None, None) => json::from_str(DEFAULT_GRAPH)?,
+    };
+    if args.patterns.is_some() {
+        let uncategorized: Vec<&str> = graph
+            .nodes
+            .iter()
+            .filter(|node| node.category == markdown::PatternImport::default().default_category)
+            .map(|node| node.label.as_str())
+            .collect();
+        if !uncategorized.is_empty() {
+            eprintln!("warning: no category declared for {}", uncategorized.join(", "));
+        }
+    }
     
-    // Create physics engine with default options
+    // Create physics engine with a fixed seed so runs are reproducible, and
+    // adaptive stepping so the strongly charged nodes can't blow up the layout
+    let options = PhysicsOptions {
+        seed: Some(42),
+        adaptive_step: Some(Default::default()),
+        boundary: Some(Boundary::Clamp),
+        ..Default::default()
+    };
     let mut engine = PhysicsEngine::new(
-        pattern_data.0,
-        pattern_data.1,
-        None
-    );
+        graph.nodes,
+        graph.links,
+        Some(options)
+    )?;
     
     // Initialize standard forces
     engine.initialize_standard_forces();
@@ -30,31 +154,31 @@ fn main() {
     
     // Run simulation for 10 steps
     println!("\nRunning simulation for 10 steps...");
-    engine.run_simulation(10);
+    engine.run_simulation(10)?;
     let state10 = engine.get_state();
     log_state(&state10, "step10");
     
     // Run simulation for 50 more steps
     println!("\nRunning simulation for 50 more steps...");
-    engine.run_simulation(50);
+    engine.run_simulation(50)?;
     let state60 = engine.get_state();
     log_state(&state60, "step60");
     
     // Run simulation for 100 more steps
     println!("\nRunning simulation for 100 more steps...");
-    engine.run_simulation(100);
+    engine.run_simulation(100)?;
     let state160 = engine.get_state();
     log_state(&state160, "step160");
     
     // Run until stabilized
     println!("\nRunning until stabilized...");
-    let (completed_steps, alpha) = engine.run_simulation(1000);
+    let (completed_steps, alpha) = engine.run_simulation(1000)?;
     println!("Simulation ran for {} more steps until alpha = {:.6}", completed_steps, alpha);
     let final_state = engine.get_state();
     log_state(&final_state, "final");
     
-    // Save all states to a JSON file for further analysis
-    let all_states = HashMap::from([
+    // Save all states to a JSON file for further analysis (sorted keys keep the file stable)
+    let all_states = BTreeMap::from([
         ("initial".to_string(), initial_state),
         ("step10".to_string(), state10),
         ("step60".to_string(), state60),
@@ -62,14 +186,24 @@ fn main() {
         ("final".to_string(), final_state.clone()),
     ]);
     
-    let json = serde_json::to_string_pretty(&all_states).unwrap();
-    fs::write("simulation-results-rust.json", json).expect("Unable to write file");
-    println!("\nSimulation results saved to simulation-results-rust.json");
+    if let Some(path) = &args.output {
+        fs::write(path, serde_json::to_string_pretty(&all_states)?)?;
+        println!("\nSimulation results saved to {}", path.display());
+    }
+    
+    if let Some(path) = &args.write_layout {
+        let mut layout = engine.graph();
+        layout.metadata = graph.metadata;
+        args.format.write(path, &layout)?;
+        println!("Final layout saved to {}", path.display());
+    }
     
     // Calculate metrics for the final state
     calculate_metrics(&final_state);
     
     println!("\n=== Test Complete ===");
+    
+    Ok(())
 }
 
 // Helper function to log state in a readable format
@@ -140,213 +274,3 @@ fn calculate_metrics(state: &SimulationState) {
             category, x, y, avg_dist_from_centroid);
     }
 }
-
-// Create test pattern data
-fn create_test_pattern_data() -> (Vec<Node>, Vec<Link>) {
-    // Create nodes
-    let nodes = vec![
-        // Structural patterns
-        Node {
-            id: "fractal-self-similarity".to_string(),
-            label: "Fractal Self-Similarity".to_string(),
-            category: "structural".to_string(),
-            physical_properties: PhysicalProperties {
-                mass: 5.0,
-                charge: -150.0,
-                friction: 0.2,
-                radius: 15.0,
-                fixed: false,
-            },
-            x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, fx: None, fy: None
-        },
-        Node {
-            id: "network-structure".to_string(),
-            label: "Network Structure".to_string(),
-            category: "structural".to_string(),
-            physical_properties: PhysicalProperties {
-                mass: 8.0,
-                charge: -150.0,
-                friction: 0.2,
-                radius: 20.0,
-                fixed: false,
-            },
-            x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, fx: None, fy: None
-        },
-        Node {
-            id: "hierarchical-organization".to_string(),
-            label: "Hierarchical Organization".to_string(),
-            category: "structural".to_string(),
-            physical_properties: PhysicalProperties {
-                mass: 6.0,
-                charge: -150.0,
-                friction: 0.2,
-                radius: 18.0,
-                fixed: false,
-            },
-            x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, fx: None, fy: None
-        },
-        
-        // Process patterns
-        Node {
-            id: "emergent-behavior".to_string(),
-            label: "Emergent Behavior".to_string(),
-            category: "process".to_string(),
-            physical_properties: PhysicalProperties {
-                mass: 4.0,
-                charge: -120.0,
-                friction: 0.3,
-                radius: 14.0,
-                fixed: false,
-            },
-            x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, fx: None, fy: None
-        },
-        Node {
-            id: "feedback-loops".to_string(),
-            label: "Feedback Loops".to_string(),
-            category: "process".to_string(),
-            physical_properties: PhysicalProperties {
-                mass: 5.0,
-                charge: -130.0,
-                friction: 0.3,
-                radius: 15.0,
-                fixed: false,
-            },
-            x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, fx: None, fy: None
-        },
-        Node {
-            id: "cyclical-patterns".to_string(),
-            label: "Cyclical Patterns".to_string(),
-            category: "process".to_string(),
-            physical_properties: PhysicalProperties {
-                mass: 4.0,
-                charge: -120.0,
-                friction: 0.3,
-                radius: 14.0,
-                fixed: false,
-            },
-            x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, fx: None, fy: None
-        },
-        
-        // Relationship patterns
-        Node {
-            id: "resource-distribution".to_string(),
-            label: "Resource Distribution".to_string(),
-            category: "relationship".to_string(),
-            physical_properties: PhysicalProperties {
-                mass: 3.0,
-                charge: -100.0,
-                friction: 0.4,
-                radius: 12.0,
-                fixed: false,
-            },
-            x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, fx: None, fy: None
-        },
-        Node {
-            id: "symbiosis-mutualism".to_string(),
-            label: "Symbiosis & Mutualism".to_string(),
-            category: "relationship".to_string(),
-            physical_properties: PhysicalProperties {
-                mass: 3.0,
-                charge: -100.0,
-                friction: 0.4,
-                radius: 12.0,
-                fixed: false,
-            },
-            x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, fx: None, fy: None
-        },
-    ];
-    
-    // Create links
-    let links = vec![
-        // Structural pattern relationships
-        Link {
-            source: "fractal-self-similarity".to_string(),
-            target: "hierarchical-organization".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.3,
-                length: 100.0,
-                elasticity: 0.5,
-            },
-        },
-        Link {
-            source: "fractal-self-similarity".to_string(),
-            target: "network-structure".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.3,
-                length: 100.0,
-                elasticity: 0.5,
-            },
-        },
-        Link {
-            source: "network-structure".to_string(),
-            target: "hierarchical-organization".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.3,
-                length: 100.0,
-                elasticity: 0.5,
-            },
-        },
-        
-        // Process pattern relationships
-        Link {
-            source: "emergent-behavior".to_string(),
-            target: "feedback-loops".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.4,
-                length: 100.0,
-                elasticity: 0.5,
-            },
-        },
-        Link {
-            source: "feedback-loops".to_string(),
-            target: "cyclical-patterns".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.5,
-                length: 80.0,
-                elasticity: 0.5,
-            },
-        },
-        
-        // Relationship pattern relationships
-        Link {
-            source: "resource-distribution".to_string(),
-            target: "symbiosis-mutualism".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.3,
-                length: 100.0,
-                elasticity: 0.5,
-            },
-        },
-        
-        // Cross-category relationships
-        Link {
-            source: "network-structure".to_string(),
-            target: "resource-distribution".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.2,
-                length: 150.0,
-                elasticity: 0.5,
-            },
-        },
-        Link {
-            source: "hierarchical-organization".to_string(),
-            target: "emergent-behavior".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.2,
-                length: 150.0,
-                elasticity: 0.5,
-            },
-        },
-        Link {
-            source: "feedback-loops".to_string(),
-            target: "symbiosis-mutualism".to_string(),
-            physical_properties: LinkPhysicalProperties {
-                stiffness: 0.2,
-                length: 150.0,
-                elasticity: 0.5,
-            },
-        },
-    ];
-    
-    (nodes, links)
-} 
\ No newline at end of file
@@ -1,10 +1,23 @@
-use rand::Rng;
+use crate::boundary::Boundary;
+use crate::constraints::{Constraint, ConstraintSolver};
+use crate::error::PhysicsError;
+use crate::forces::{CategoryGroupForce, CenterForce, CollisionForce, Force, ForceRegistry, GravityForce, LinkForce, ManyBodyForce};
+use crate::graph::{self, Graph, NodeIndex};
+use crate::integrator::{self, AdaptiveStep, D3Integrator, Integrator, Step};
+use crate::layout::{self, InitialLayout};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 // Core types for the physics engine
 type NodeId = String;
-type ForceFunction = fn(&mut PhysicsEngine, f64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PhysicalProperties {
@@ -12,7 +25,22 @@ pub struct PhysicalProperties {
     pub charge: f64,
     pub friction: f64,
     pub radius: f64,
+    #[serde(default)]
     pub fixed: bool,
+    // Starting position; nodes without one are placed by the initial layout
+    #[serde(default, alias = "initialPosition", skip_serializing_if = "Option::is_none")]
+    pub initial_position: Option<Position>,
+}
+
+impl PhysicalProperties {
+    // Non-positive or non-finite masses are treated as unit mass
+    pub fn inverse_mass(&self) -> f64 {
+        if self.mass > 0.0 && self.mass.is_finite() {
+            1.0 / self.mass
+        } else {
+            1.0
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -20,28 +48,109 @@ pub struct LinkPhysicalProperties {
     pub stiffness: f64,
     pub length: f64,
     pub elasticity: f64,
+    // How the spring responds to being stretched or compressed
+    #[serde(default, alias = "springModel")]
+    pub spring_model: SpringModel,
+    // One-way links (false) point from source to target; forces may treat
+    // the two ends differently
+    #[serde(default = "default_bidirectional")]
+    pub bidirectional: bool,
+}
+
+fn default_bidirectional() -> bool {
+    true
+}
+
+// Force law of a link spring. `elasticity` (e) controls how readily the link
+// gives way when stretched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpringModel {
+    // Hooke's law, the same in both directions; elasticity is ignored
+    #[default]
+    Linear,
+    // Hooke's law, with the stiffness divided by (1 + e) while stretched
+    Asymmetric,
+    // Eades-style spring, stiffness · length · ln(distance / length), also
+    // softened by (1 + e) while stretched
+    Logarithmic,
+    // Hooke's law up to length · (1 + e); beyond that the link acts as a rigid rod
+    MaxStretch,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Node {
     pub id: NodeId,
+    #[serde(default)]
     pub label: String,
+    #[serde(default)]
     pub category: String,
+    #[serde(alias = "physicalProperties")]
     pub physical_properties: PhysicalProperties,
+    // Anything else known about the node (description, domains, ...); the
+    // engine doesn't read it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
     // Physics state
+    #[serde(default)]
     pub x: f64,
+    #[serde(default)]
     pub y: f64,
+    #[serde(default)]
     pub vx: f64,
+    #[serde(default)]
     pub vy: f64,
+    #[serde(default)]
     pub fx: Option<f64>,
+    #[serde(default)]
     pub fy: Option<f64>,
 }
 
+impl Node {
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.vx.is_finite() && self.vy.is_finite()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Link {
     pub source: NodeId,
     pub target: NodeId,
+    #[serde(default, alias = "relationshipType", alias = "type")]
+    pub relationship_type: RelationshipType,
+    #[serde(alias = "physicalProperties")]
     pub physical_properties: LinkPhysicalProperties,
+    // Anything else known about the link; the engine doesn't read it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+}
+
+// What a link means. Whether a link is one-way is up to
+// `LinkPhysicalProperties::bidirectional`; `is_one_way` gives the usual
+// direction, which graph readers use when a link doesn't say.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationshipType {
+    #[default]
+    Related,
+    Strong,
+    Weak,
+    // Source causes or drives the target
+    Causal,
+    // Part of a feedback loop
+    Feedback,
+    // Source is the parent of the target
+    Hierarchical,
+    // Any type this engine doesn't know about
+    #[serde(other)]
+    Other,
+}
+
+impl RelationshipType {
+    // Causal, feedback and hierarchical links point from source to target
+    pub fn is_one_way(self) -> bool {
+        matches!(self, RelationshipType::Causal | RelationshipType::Feedback | RelationshipType::Hierarchical)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -73,8 +182,20 @@ pub struct PhysicsEngine {
     pub nodes: Vec<Node>,
     pub links: Vec<Link>,
     pub options: PhysicsOptions,
-    pub forces: HashMap<String, ForceFunction>,
+    pub forces: ForceRegistry,
+    // Layout constraints projected after every step; added with `add_constraint`
+    pub constraints: ConstraintSolver,
+    pub integrator: Box<dyn Integrator>,
     pub tick_count: usize,
+    // Node id -> position in `nodes`, rebuilt whenever the graph changes
+    node_index: NodeIndex,
+    // (source, target) index of every link, resolved with `node_index`
+    endpoints: Vec<(usize, usize)>,
+    // Kinetic energy after the last tick, used by the adaptive step
+    // controller; None until the current graph has run a tick
+    kinetic_energy: Option<f64>,
+    // Source of all randomness in the engine (placement and force jitter)
+    rng: Box<dyn RngCore>,
 }
 
 #[derive(Clone, Debug)]
@@ -84,8 +205,19 @@ pub struct PhysicsOptions {
     pub alpha_decay: f64,
     pub alpha_target: f64,
     pub velocity_decay: f64,
+    // Time step handed to the integrator; 1.0 matches d3. Updated every tick
+    // when adaptive stepping is enabled.
+    pub dt: f64,
+    // Adaptive step-size control; None keeps dt constant
+    pub adaptive_step: Option<AdaptiveStep>,
     pub width: f64,
     pub height: f64,
+    // Walls around the width x height canvas; None lets nodes go anywhere
+    pub boundary: Option<Boundary>,
+    // Seed for the engine's random generator; None draws one from the OS
+    pub seed: Option<u64>,
+    // Placement of nodes that have no initial position
+    pub initial_layout: InitialLayout,
 }
 
 impl Default for PhysicsOptions {
@@ -96,283 +228,302 @@ impl Default for PhysicsOptions {
             alpha_decay: 0.0228,
             alpha_target: 0.0,
             velocity_decay: 0.4,
+            dt: 1.0,
+            adaptive_step: None,
             width: 1000.0,
             height: 1000.0,
+            boundary: None,
+            seed: None,
+            initial_layout: InitialLayout::default(),
         }
     }
 }
 
 impl PhysicsEngine {
-    pub fn new(nodes: Vec<Node>, links: Vec<Link>, options: Option<PhysicsOptions>) -> Self {
-        let mut rng = rand::thread_rng();
+    // Builds an engine over a validated graph: node ids must be unique and
+    // every link must connect two different, existing nodes
+    pub fn new(nodes: Vec<Node>, links: Vec<Link>, options: Option<PhysicsOptions>) -> Result<Self, PhysicsError> {
         let options = options.unwrap_or_default();
+        let rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
         
-        // Initialize random positions if not set
-        let nodes = nodes
-            .into_iter()
-            .map(|mut node| {
-                if node.x == 0.0 {
-                    node.x = rng.gen::<f64>() * options.width;
-                }
-                if node.y == 0.0 {
-                    node.y = rng.gen::<f64>() * options.height;
-                }
-                
-                node.vx = 0.0;
-                node.vy = 0.0;
-                
-                node
-            })
-            .collect();
-
-        PhysicsEngine {
-            nodes,
-            links,
-            options,
-            forces: HashMap::new(),
+        Self::with_rng(nodes, links, Some(options), rng)
+    }
+    
+    // Like `new`, but draws all randomness from the given generator
+    pub fn with_rng(nodes: Vec<Node>, links: Vec<Link>, options: Option<PhysicsOptions>, rng: impl RngCore + 'static) -> Result<Self, PhysicsError> {
+        let mut engine = PhysicsEngine {
+            nodes: Vec::new(),
+            links: Vec::new(),
+            options: options.unwrap_or_default(),
+            forces: ForceRegistry::new(),
+            constraints: ConstraintSolver::default(),
+            integrator: Box::new(D3Integrator),
             tick_count: 0,
+            node_index: NodeIndex::new(),
+            endpoints: Vec::new(),
+            kinetic_energy: None,
+            rng: Box::new(rng),
+        };
+        
+        engine.set_graph(nodes, links)?;
+        Ok(engine)
+    }
+    
+    // Replaces the graph. Nodes whose id was already simulated keep their
+    // position and velocity; new nodes start at their initial position or are
+    // placed by the initial layout. Every force is re-initialized.
+    pub fn set_graph(&mut self, mut nodes: Vec<Node>, links: Vec<Link>) -> Result<(), PhysicsError> {
+        let (node_index, endpoints) = graph::resolve(&nodes, &links)?;
+        self.constraints.rebind(&node_index)?;
+        
+        let mut unplaced = Vec::new();
+        for (i, node) in nodes.iter_mut().enumerate() {
+            if let Some(existing) = self.find_node_index(&node.id).map(|index| &self.nodes[index]) {
+                node.x = existing.x;
+                node.y = existing.y;
+                node.vx = existing.vx;
+                node.vy = existing.vy;
+                continue;
+            }
+            
+            match node.physical_properties.initial_position {
+                Some(position) => {
+                    node.x = position.x;
+                    node.y = position.y;
+                }
+                None => unplaced.push(i),
+            }
+            node.vx = 0.0;
+            node.vy = 0.0;
         }
+        
+        layout::place_nodes(
+            &self.options.initial_layout,
+            &mut nodes,
+            &unplaced,
+            &endpoints,
+            self.options.width,
+            self.options.height,
+            &mut self.rng,
+        );
+        
+        self.nodes = nodes;
+        self.links = links;
+        self.node_index = node_index;
+        self.endpoints = endpoints;
+        self.kinetic_energy = None;
+        self.forces.initialize(&self.nodes, &self.links, &self.endpoints);
+        
+        Ok(())
+    }
+    
+    // Current nodes (with their positions) and links, ready to be written out
+    pub fn graph(&self) -> Graph {
+        Graph {
+            nodes: self.nodes.clone(),
+            links: self.links.clone(),
+            ..Default::default()
+        }
+    }
+    
+    // Attaches a layout constraint; every node id it names must exist
+    pub fn add_constraint(&mut self, constraint: Constraint) -> Result<(), PhysicsError> {
+        self.constraints.add(constraint, &self.node_index)
+    }
+    
+    pub fn clear_constraints(&mut self) {
+        self.constraints.clear();
+    }
+    
+    pub fn set_integrator(&mut self, integrator: impl Integrator + 'static) {
+        self.integrator = Box::new(integrator);
     }
     
     pub fn find_node_index(&self, id: &str) -> Option<usize> {
-        self.nodes.iter().position(|node| node.id == id)
+        self.node_index.get(id).copied()
+    }
+    
+    // Appends a force to the pipeline, or replaces the force of the same name in place
+    pub fn add_force(&mut self, name: &str, force: impl Force + 'static) {
+        let force = self.prepare_force(force);
+        self.forces.insert(name, force);
+    }
+    
+    pub fn insert_force_before(&mut self, anchor: &str, name: &str, force: impl Force + 'static) -> Result<(), PhysicsError> {
+        let force = self.prepare_force(force);
+        self.forces.insert_before(anchor, name, force)
+    }
+    
+    pub fn insert_force_after(&mut self, anchor: &str, name: &str, force: impl Force + 'static) -> Result<(), PhysicsError> {
+        let force = self.prepare_force(force);
+        self.forces.insert_after(anchor, name, force)
+    }
+    
+    pub fn replace_force(&mut self, name: &str, force: impl Force + 'static) -> Result<Box<dyn Force>, PhysicsError> {
+        let force = self.prepare_force(force);
+        self.forces.replace(name, force)
+    }
+    
+    pub fn remove_force(&mut self, name: &str) -> Option<Box<dyn Force>> {
+        self.forces.remove(name)
     }
     
-    pub fn add_force(&mut self, name: &str, force_fn: ForceFunction) {
-        self.forces.insert(name.to_string(), force_fn);
+    pub fn enable_force(&mut self, name: &str) -> Result<(), PhysicsError> {
+        self.forces.set_enabled(name, true)
     }
     
-    pub fn tick(&mut self) -> bool {
+    pub fn disable_force(&mut self, name: &str) -> Result<(), PhysicsError> {
+        self.forces.set_enabled(name, false)
+    }
+    
+    fn prepare_force(&self, force: impl Force + 'static) -> Box<dyn Force> {
+        let mut force = Box::new(force);
+        force.initialize(&self.nodes, &self.links, &self.endpoints);
+        force
+    }
+    
+    // Advances the simulation by one step. Returns Ok(false) once the
+    // simulation has cooled down, and an error as soon as any node's state
+    // becomes NaN or infinite.
+    pub fn tick(&mut self) -> Result<bool, PhysicsError> {
         // Skip if simulation has cooled down
         if self.options.alpha < self.options.alpha_min {
-            return false;
+            return Ok(false);
         }
         
         self.tick_count += 1;
         
-        // Apply forces to calculate acceleration
         let alpha = self.options.alpha;
+        let step = Step {
+            dt: self.options.dt,
+            velocity_decay: self.options.velocity_decay,
+        };
+        
+        // Keep fixed nodes at rest so no scheme moves them
+        for node in &mut self.nodes {
+            if node.physical_properties.fixed {
+                node.vx = 0.0;
+                node.vy = 0.0;
+            }
+        }
         
-        // Collect force functions to avoid borrowing conflict
-        let force_fns: Vec<ForceFunction> = self.forces.values().copied().collect();
+        let previous_positions: Vec<(f64, f64)> = self.nodes.iter().map(|node| (node.x, node.y)).collect();
         
-        // Apply each force
-        for force_fn in force_fns {
-            force_fn(self, alpha);
+        // Advance positions and velocities with the configured integrator
+        let forces = &mut self.forces;
+        let rng = &mut self.rng;
+        let mut accelerations = |nodes: &mut [Node]| Self::accelerations(forces, nodes, alpha, rng);
+        self.integrator.step(&mut self.nodes, &step, &mut accelerations);
+        
+        if let Some(adaptive) = &self.options.adaptive_step {
+            adaptive.limit(&mut self.nodes, &previous_positions, step.dt);
+        }
+        
+        // Forces that move nodes directly, such as centering
+        self.forces.adjust_positions(&mut self.nodes);
+        
+        // Layout constraints, then the canvas walls
+        self.constraints.project(&mut self.nodes);
+        
+        if let Some(boundary) = &self.options.boundary {
+            boundary.apply(&mut self.nodes, self.options.width, self.options.height, step.dt);
         }
         
-        // Update positions using Velocity Verlet integration
+        // Re-apply pinned coordinates
         for node in &mut self.nodes {
             if let Some(fx) = node.fx {
                 node.x = fx;
                 node.vx = 0.0;
-            } else {
-                node.vx *= self.options.velocity_decay;
-                node.x += node.vx;
             }
-            
             if let Some(fy) = node.fy {
                 node.y = fy;
                 node.vy = 0.0;
-            } else {
-                node.vy *= self.options.velocity_decay;
-                node.y += node.vy;
             }
         }
         
+        // Stop before a NaN spreads through the whole simulation
+        if let Some(node) = self.nodes.iter().find(|node| !node.is_finite()) {
+            return Err(PhysicsError::NonFinite {
+                tick: self.tick_count,
+                node: node.id.clone(),
+            });
+        }
+        
+        // Adapt the time step to how the kinetic energy changed
+        let kinetic_energy = integrator::kinetic_energy(&self.nodes);
+        if let Some(adaptive) = &self.options.adaptive_step {
+            self.options.dt = adaptive.next_dt(self.options.dt, self.kinetic_energy, kinetic_energy);
+        }
+        self.kinetic_energy = Some(kinetic_energy);
+        
         // Cool down simulation
         self.options.alpha += (self.options.alpha_target - self.options.alpha) * self.options.alpha_decay;
         
-        true
+        Ok(true)
     }
     
-    pub fn initialize_standard_forces(&mut self) {
-        self.add_force("charge", Self::many_body_force);
-        self.add_force("link", Self::link_force);
-        self.add_force("center", Self::center_force);
-        self.add_force("collision", Self::collision_force);
-    }
-    
-    pub fn many_body_force(engine: &mut PhysicsEngine, alpha: f64) {
-        let strength = -30.0;
-        let node_count = engine.nodes.len();
+    // Applies every enabled force in registration order and returns the
+    // velocity change they produce as an acceleration; the registry has
+    // already divided it by mass where that applies. Node velocities are
+    // restored afterwards so integrators can evaluate intermediate states.
+    fn accelerations(forces: &mut ForceRegistry, nodes: &mut [Node], alpha: f64, rng: &mut dyn RngCore) -> Vec<(f64, f64)> {
+        let velocities: Vec<(f64, f64)> = nodes.iter().map(|node| (node.vx, node.vy)).collect();
         
-        for i in 0..node_count {
-            let (charge_i, x_i, y_i) = {
-                let node = &engine.nodes[i];
-                (node.physical_properties.charge, node.x, node.y)
-            };
-            
-            for j in (i+1)..node_count {
-                let (charge_j, x_j, y_j) = {
-                    let node = &engine.nodes[j];
-                    (node.physical_properties.charge, node.x, node.y)
-                };
-                
-                // Calculate distance vector
-                let dx = x_j - x_i;
-                let dy = y_j - y_i;
-                let distance_squared = dx * dx + dy * dy;
+        forces.apply(nodes, alpha, rng);
+        
+        nodes
+            .iter_mut()
+            .zip(velocities)
+            .map(|(node, (vx, vy))| {
+                let mut acceleration = (node.vx - vx, node.vy - vy);
+                node.vx = vx;
+                node.vy = vy;
                 
-                // Skip if nodes are at the same position
-                if distance_squared == 0.0 {
-                    continue;
+                // Pinned axes don't accelerate
+                if node.physical_properties.fixed {
+                    acceleration = (0.0, 0.0);
                 }
-                
-                // Calculate repulsive force (inverse square law)
-                let distance = distance_squared.sqrt();
-                let force = strength * charge_i * charge_j / distance_squared;
-                
-                // Apply force to both nodes (Newton's third law)
-                let unit_x = dx / distance;
-                let unit_y = dy / distance;
-                
-                // Apply forces
-                let force_x = unit_x * force * alpha;
-                let force_y = unit_y * force * alpha;
-                
-                // Update velocities for both nodes
-                engine.nodes[i].vx -= force_x;
-                engine.nodes[i].vy -= force_y;
-                engine.nodes[j].vx += force_x;
-                engine.nodes[j].vy += force_y;
-            }
-        }
-    }
-    
-    pub fn link_force(engine: &mut PhysicsEngine, alpha: f64) {
-        for link in &engine.links.clone() {
-            if let (Some(source_idx), Some(target_idx)) = (
-                engine.find_node_index(&link.source),
-                engine.find_node_index(&link.target),
-            ) {
-                let (x1, y1, x2, y2) = {
-                    let source = &engine.nodes[source_idx];
-                    let target = &engine.nodes[target_idx];
-                    (source.x, source.y, target.x, target.y)
-                };
-                
-                // Calculate distance vector
-                let dx = x2 - x1;
-                let dy = y2 - y1;
-                let distance = (dx * dx + dy * dy).sqrt();
-                
-                // Skip if nodes are at the same position
-                if distance == 0.0 {
-                    continue;
+                if node.fx.is_some() {
+                    acceleration.0 = 0.0;
+                }
+                if node.fy.is_some() {
+                    acceleration.1 = 0.0;
                 }
                 
-                // Calculate spring force (Hooke's law)
-                let natural_length = link.physical_properties.length;
-                let stiffness = link.physical_properties.stiffness;
-                let displacement = distance - natural_length;
-                let spring_force = stiffness * displacement;
-                
-                // Apply force proportional to displacement
-                let unit_x = dx / distance;
-                let unit_y = dy / distance;
-                
-                let fx = spring_force * unit_x * alpha;
-                let fy = spring_force * unit_y * alpha;
-                
-                // Apply forces to source and target
-                engine.nodes[source_idx].vx += fx;
-                engine.nodes[source_idx].vy += fy;
-                engine.nodes[target_idx].vx -= fx;
-                engine.nodes[target_idx].vy -= fy;
-            }
-        }
-    }
-    
-    pub fn center_force(engine: &mut PhysicsEngine, alpha: f64) {
-        let center_x = engine.options.width / 2.0;
-        let center_y = engine.options.height / 2.0;
-        let strength = 0.1;
-        
-        for node in &mut engine.nodes {
-            node.vx += (center_x - node.x) * strength * alpha;
-            node.vy += (center_y - node.y) * strength * alpha;
-        }
+                acceleration
+            })
+            .collect()
     }
     
-    pub fn collision_force(engine: &mut PhysicsEngine, alpha: f64) {
-        let node_count = engine.nodes.len();
+    pub fn initialize_standard_forces(&mut self) {
+        let (center_x, center_y) = (self.options.width / 2.0, self.options.height / 2.0);
         
-        for i in 0..node_count {
-            let (radius_i, x_i, y_i) = {
-                let node = &engine.nodes[i];
-                (node.physical_properties.radius, node.x, node.y)
-            };
-            
-            for j in (i+1)..node_count {
-                let (radius_j, x_j, y_j) = {
-                    let node = &engine.nodes[j];
-                    (node.physical_properties.radius, node.x, node.y)
-                };
-                
-                // Calculate distance vector
-                let dx = x_j - x_i;
-                let dy = y_j - y_i;
-                let distance = (dx * dx + dy * dy).sqrt();
-                
-                // Skip if nodes are far apart
-                let min_distance = radius_i + radius_j;
-                if distance >= min_distance {
-                    continue;
-                }
-                
-                // Calculate collision response
-                let unit_x = dx / distance;
-                let unit_y = dy / distance;
-                
-                // Move nodes apart
-                let separation = min_distance - distance;
-                let move_x = unit_x * separation * 0.5;
-                let move_y = unit_y * separation * 0.5;
-                
-                // Apply collision forces
-                engine.nodes[i].vx -= move_x * alpha;
-                engine.nodes[i].vy -= move_y * alpha;
-                engine.nodes[j].vx += move_x * alpha;
-                engine.nodes[j].vy += move_y * alpha;
-            }
-        }
+        self.add_force("charge", ManyBodyForce::default());
+        self.add_force("link", LinkForce::default());
+        self.add_force("gravity", GravityForce::new(center_x, center_y));
+        self.add_force("collision", CollisionForce::default());
+        self.add_force("center", CenterForce::new(center_x, center_y));
     }
     
     pub fn add_category_group_force(&mut self) {
-        self.add_force("category_group", Self::category_group_force);
-    }
-    
-    pub fn category_group_force(engine: &mut PhysicsEngine, alpha: f64) {
-        // Group nodes by pattern category
-        let width = engine.options.width;
-        let height = engine.options.height;
-        
-        let category_groups = HashMap::from([
-            ("structural".to_string(), (width * 0.25, height * 0.25)),
-            ("process".to_string(), (width * 0.75, height * 0.25)),
-            ("relationship".to_string(), (width * 0.5, height * 0.75)),
-        ]);
-        
-        for node in &mut engine.nodes {
-            if let Some(&(target_x, target_y)) = category_groups.get(&node.category) {
-                node.vx += (target_x - node.x) * 0.01 * alpha;
-                node.vy += (target_y - node.y) * 0.01 * alpha;
-            }
-        }
+        let category_group = CategoryGroupForce::new(self.options.width, self.options.height);
+        self.add_force("category_group", category_group);
     }
     
-    pub fn run_simulation(&mut self, steps: usize) -> (usize, f64) {
+    pub fn run_simulation(&mut self, steps: usize) -> Result<(usize, f64), PhysicsError> {
         let mut completed_steps = 0;
         
         for _ in 0..steps {
-            if !self.tick() {
+            if !self.tick()? {
                 break;
             }
             completed_steps += 1;
         }
         
-        (completed_steps, self.options.alpha)
+        Ok((completed_steps, self.options.alpha))
     }
     
     pub fn get_state(&self) -> SimulationState {
@@ -404,4 +555,183 @@ impl PhysicsEngine {
             alpha: self.options.alpha,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forces::LinkDirection;
+    use crate::formats::json;
+    
+    // Positions and velocities after running the demo graph, with every node
+    // left to the random initial layout
+    fn trajectory(seed: u64) -> Vec<(f64, f64, f64, f64)> {
+        let mut graph = json::from_str(include_str!("../data/pattern-network.json")).unwrap();
+        for node in &mut graph.nodes {
+            node.physical_properties.initial_position = None;
+        }
+        let options = PhysicsOptions {
+            seed: Some(seed),
+            initial_layout: InitialLayout::Random,
+            ..PhysicsOptions::default()
+        };
+        
+        let mut engine = PhysicsEngine::new(graph.nodes, graph.links, Some(options)).unwrap();
+        engine.initialize_standard_forces();
+        engine.add_category_group_force();
+        engine.run_simulation(100).unwrap();
+        engine.nodes.iter().map(|node| (node.x, node.y, node.vx, node.vy)).collect()
+    }
+    
+    fn node(id: &str, x: f64, y: f64, mass: f64) -> Node {
+        Node {
+            id: id.to_string(),
+            label: String::new(),
+            category: String::new(),
+            physical_properties: PhysicalProperties {
+                mass,
+                charge: 0.0,
+                friction: 0.0,
+                radius: 15.0,
+                fixed: false,
+                initial_position: Some(Position { x, y }),
+            },
+            metadata: BTreeMap::new(),
+            x: 0.0,
+            y: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            fx: None,
+            fy: None,
+        }
+    }
+    
+    // Undamped engine over the given nodes, with no forces yet
+    fn engine(nodes: Vec<Node>, links: Vec<Link>) -> PhysicsEngine {
+        let options = PhysicsOptions {
+            velocity_decay: 1.0,
+            seed: Some(1),
+            ..PhysicsOptions::default()
+        };
+        PhysicsEngine::new(nodes, links, Some(options)).unwrap()
+    }
+    
+    fn distance(engine: &PhysicsEngine) -> f64 {
+        let (a, b) = (&engine.nodes[0], &engine.nodes[1]);
+        (a.x - b.x).hypot(a.y - b.y)
+    }
+    
+    #[test]
+    fn collision_resolves_overlaps_of_heavy_nodes() {
+        let mut engine = engine(vec![node("a", 0.0, 0.0, 6.0), node("b", 10.0, 0.0, 6.0)], Vec::new());
+        engine.add_force("collision", CollisionForce::default());
+        engine.tick().unwrap();
+        assert!((distance(&engine) - 30.0).abs() < 1e-9, "{}", distance(&engine));
+    }
+    
+    #[test]
+    fn collision_moves_only_the_free_node_away_from_a_fixed_or_pinned_one() {
+        let mut fixed = node("a", 0.0, 0.0, 1.0);
+        fixed.physical_properties.fixed = true;
+        let mut pinned = node("a", 0.0, 0.0, 1.0);
+        pinned.fx = Some(0.0);
+        pinned.fy = Some(0.0);
+        
+        for anchor in [fixed, pinned] {
+            let mut engine = engine(vec![anchor, node("b", 10.0, 0.0, 1.0)], Vec::new());
+            engine.add_force("collision", CollisionForce::default());
+            engine.tick().unwrap();
+            assert_eq!((engine.nodes[0].x, engine.nodes[0].y), (0.0, 0.0));
+            assert!((distance(&engine) - 30.0).abs() < 1e-9, "{}", distance(&engine));
+        }
+    }
+    
+    fn rod(source: &str, target: &str) -> Link {
+        Link {
+            source: source.to_string(),
+            target: target.to_string(),
+            relationship_type: RelationshipType::Strong,
+            physical_properties: LinkPhysicalProperties {
+                stiffness: 0.3,
+                length: 100.0,
+                elasticity: 0.2,
+                spring_model: SpringModel::MaxStretch,
+                bidirectional: true,
+            },
+            metadata: BTreeMap::new(),
+        }
+    }
+    
+    #[test]
+    fn max_stretch_links_never_end_a_tick_overstretched() {
+        for link_force in [LinkForce::default(), LinkForce::d3()] {
+            let nodes = vec![node("a", 0.0, 0.0, 5.0), node("b", 300.0, 0.0, 5.0)];
+            let mut engine = engine(nodes, vec![rod("a", "b")]);
+            engine.add_force("link", link_force);
+            for _ in 0..5 {
+                engine.tick().unwrap();
+                assert!(distance(&engine) <= 120.0 + 1e-9, "{}", distance(&engine));
+            }
+        }
+    }
+    
+    #[test]
+    fn max_stretch_moves_only_the_free_end() {
+        let mut nodes = vec![node("a", 0.0, 0.0, 1.0), node("b", 300.0, 0.0, 1.0)];
+        nodes[0].physical_properties.fixed = true;
+        let mut engine = engine(nodes, vec![rod("a", "b")]);
+        engine.add_force("link", LinkForce::default());
+        engine.tick().unwrap();
+        assert_eq!((engine.nodes[0].x, engine.nodes[0].y), (0.0, 0.0));
+        assert!((distance(&engine) - 120.0).abs() < 1e-9, "{}", distance(&engine));
+    }
+    
+    #[test]
+    fn target_only_links_leave_the_source_of_one_way_links_in_place() {
+        for bidirectional in [false, true] {
+            let mut link = rod("a", "b");
+            link.physical_properties.spring_model = SpringModel::Linear;
+            link.physical_properties.bidirectional = bidirectional;
+            let nodes = vec![node("a", 0.0, 0.0, 1.0), node("b", 300.0, 0.0, 1.0)];
+            let mut engine = engine(nodes, vec![link]);
+            let mut link_force = LinkForce::default();
+            link_force.direction = LinkDirection::TargetOnly;
+            engine.add_force("link", link_force);
+            engine.tick().unwrap();
+            
+            assert_eq!(engine.nodes[0].x == 0.0, !bidirectional);
+            assert!(engine.nodes[1].x < 300.0);
+        }
+    }
+    
+    #[test]
+    fn adaptive_step_keeps_dt_on_the_first_tick() {
+        let mut engine = engine(vec![node("a", 0.0, 0.0, 1.0), node("b", 10.0, 0.0, 1.0)], Vec::new());
+        engine.options.adaptive_step = Some(AdaptiveStep::default());
+        engine.add_force("collision", CollisionForce::default());
+        engine.tick().unwrap();
+        assert_eq!(engine.options.dt, 1.0);
+        
+        // A new graph starts over
+        engine.options.dt = 0.5;
+        let nodes = vec![node("c", 0.0, 0.0, 1.0), node("d", 10.0, 0.0, 1.0)];
+        engine.set_graph(nodes, Vec::new()).unwrap();
+        engine.tick().unwrap();
+        assert_eq!(engine.options.dt, 0.5);
+    }
+    
+    #[test]
+    fn same_seed_gives_identical_trajectories() {
+        let first = trajectory(42);
+        let second = trajectory(42);
+        // Bit-identical, not merely close
+        let bits = |run: &[(f64, f64, f64, f64)]| -> Vec<[u64; 4]> {
+            run.iter().map(|&(x, y, vx, vy)| [x.to_bits(), y.to_bits(), vx.to_bits(), vy.to_bits()]).collect()
+        };
+        assert_eq!(bits(&first), bits(&second));
+    }
+    
+    #[test]
+    fn different_seeds_give_different_trajectories() {
+        assert_ne!(trajectory(1), trajectory(2));
+    }
+}
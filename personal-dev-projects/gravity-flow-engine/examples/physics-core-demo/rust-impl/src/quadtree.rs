@@ -0,0 +1,240 @@
+// Region quadtree used to approximate long-range forces (Barnes-Hut)
+
+// Sentinel for missing children / empty leaves
+const NONE: usize = usize::MAX;
+
+// Subdivision stops at this depth; anything deeper is chained in the leaf
+const MAX_DEPTH: usize = 48;
+
+#[derive(Clone, Debug)]
+pub struct Cell {
+    pub x0: f64,
+    pub y0: f64,
+    pub size: f64,
+    children: [usize; 4],
+    // First body stored in this leaf (further bodies are chained via `next`)
+    body: usize,
+}
+
+impl Cell {
+    fn new(x0: f64, y0: f64, size: f64) -> Self {
+        Cell {
+            x0,
+            y0,
+            size,
+            children: [NONE; 4],
+            body: NONE,
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.children.iter().all(|&child| child == NONE)
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = usize> + '_ {
+        self.children.iter().copied().filter(|&child| child != NONE)
+    }
+
+    fn quadrant(&self, x: f64, y: f64) -> usize {
+        let half = self.size / 2.0;
+        let right = (x >= self.x0 + half) as usize;
+        let bottom = (y >= self.y0 + half) as usize;
+        bottom << 1 | right
+    }
+}
+
+// Aggregated value of all bodies inside a cell, located at their
+// |value|-weighted centroid
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Aggregate {
+    pub value: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct QuadTree {
+    cells: Vec<Cell>,
+    positions: Vec<(f64, f64)>,
+    // Linked list of bodies sharing a leaf (coincident points)
+    next: Vec<usize>,
+}
+
+impl QuadTree {
+    pub fn build(positions: &[(f64, f64)]) -> Self {
+        let mut tree = QuadTree {
+            cells: Vec::with_capacity(positions.len() * 2),
+            positions: positions.to_vec(),
+            next: vec![NONE; positions.len()],
+        };
+
+        // Bounding square of all finite points
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y) in positions {
+            if x.is_finite() && y.is_finite() {
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+            }
+        }
+
+        if min_x > max_x {
+            return tree;
+        }
+
+        // Pad slightly so points on the far edge fall inside the root
+        let size = (max_x - min_x).max(max_y - min_y).max(1e-9) * (1.0 + 1e-9);
+        tree.cells.push(Cell::new(min_x, min_y, size));
+
+        for (index, &(x, y)) in positions.iter().enumerate() {
+            if x.is_finite() && y.is_finite() {
+                tree.insert(index, x, y);
+            }
+        }
+
+        tree
+    }
+
+    fn insert(&mut self, index: usize, x: f64, y: f64) {
+        let mut cell = 0;
+        let mut depth = 0;
+
+        loop {
+            if !self.cells[cell].is_leaf() {
+                let quadrant = self.cells[cell].quadrant(x, y);
+                cell = self.child(cell, quadrant);
+                depth += 1;
+                continue;
+            }
+
+            let existing = self.cells[cell].body;
+            if existing == NONE {
+                self.cells[cell].body = index;
+                return;
+            }
+
+            // Chain coincident points instead of subdividing forever
+            let (ex, ey) = self.positions[existing];
+            if (ex == x && ey == y) || depth >= MAX_DEPTH {
+                self.next[index] = self.next[existing];
+                self.next[existing] = index;
+                return;
+            }
+
+            // Split the leaf and push its bodies one level down
+            self.cells[cell].body = NONE;
+            let quadrant = self.cells[cell].quadrant(ex, ey);
+            let child = self.child(cell, quadrant);
+            self.cells[child].body = existing;
+        }
+    }
+
+    fn child(&mut self, cell: usize, quadrant: usize) -> usize {
+        if self.cells[cell].children[quadrant] == NONE {
+            let parent = &self.cells[cell];
+            let half = parent.size / 2.0;
+            let x0 = parent.x0 + if quadrant & 1 == 1 { half } else { 0.0 };
+            let y0 = parent.y0 + if quadrant & 2 == 2 { half } else { 0.0 };
+            self.cells.push(Cell::new(x0, y0, half));
+            let child = self.cells.len() - 1;
+            self.cells[cell].children[quadrant] = child;
+        }
+        self.cells[cell].children[quadrant]
+    }
+
+    pub fn cell(&self, index: usize) -> &Cell {
+        &self.cells[index]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // Bodies stored directly in a leaf cell
+    pub fn bodies(&self, cell: &Cell) -> impl Iterator<Item = usize> + '_ {
+        let mut current = cell.body;
+        std::iter::from_fn(move || {
+            if current == NONE {
+                return None;
+            }
+            let body = current;
+            current = self.next[body];
+            Some(body)
+        })
+    }
+
+    // Sum `values` per cell. Children are always created after their parent,
+    // so a reverse sweep visits every cell after all of its descendants.
+    pub fn aggregate(&self, values: &[f64]) -> Vec<Aggregate> {
+        let n = self.cells.len();
+        let mut aggregates = vec![Aggregate::default(); n];
+        // Per cell: (|value| weight, weighted x, weighted y, body count, x sum, y sum)
+        let mut sums = vec![(0.0, 0.0, 0.0, 0usize, 0.0, 0.0); n];
+
+        for index in (0..n).rev() {
+            let cell = &self.cells[index];
+            let mut value = 0.0;
+            let mut sum = (0.0, 0.0, 0.0, 0usize, 0.0, 0.0);
+
+            if cell.is_leaf() {
+                for body in self.bodies(cell) {
+                    let (x, y) = self.positions[body];
+                    let weight = values[body].abs();
+                    value += values[body];
+                    sum.0 += weight;
+                    sum.1 += weight * x;
+                    sum.2 += weight * y;
+                    sum.3 += 1;
+                    sum.4 += x;
+                    sum.5 += y;
+                }
+            } else {
+                for child in cell.children() {
+                    let c = sums[child];
+                    value += aggregates[child].value;
+                    sum.0 += c.0;
+                    sum.1 += c.1;
+                    sum.2 += c.2;
+                    sum.3 += c.3;
+                    sum.4 += c.4;
+                    sum.5 += c.5;
+                }
+            }
+
+            // Fall back to the plain centroid for weightless cells
+            let (x, y) = if sum.0 > 0.0 {
+                (sum.1 / sum.0, sum.2 / sum.0)
+            } else if sum.3 > 0 {
+                (sum.4 / sum.3 as f64, sum.5 / sum.3 as f64)
+            } else {
+                (cell.x0 + cell.size / 2.0, cell.y0 + cell.size / 2.0)
+            };
+
+            aggregates[index] = Aggregate { value, x, y };
+            sums[index] = sum;
+        }
+
+        aggregates
+    }
+
+    // Depth-first traversal from the root; the callback returns true to
+    // descend into the children of the visited cell.
+    pub fn visit<F>(&self, mut callback: F)
+    where
+        F: FnMut(usize, &Cell) -> bool,
+    {
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let cell = &self.cells[index];
+            if callback(index, cell) {
+                stack.extend(cell.children());
+            }
+        }
+    }
+}
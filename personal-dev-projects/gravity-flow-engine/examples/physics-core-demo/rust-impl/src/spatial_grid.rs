@@ -0,0 +1,99 @@
+// Uniform grid broad phase for short-range interactions such as collisions
+
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct SpatialGrid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    // Any two points closer than `cell_size` end up in neighbouring cells
+    pub fn build(positions: &[(f64, f64)], cell_size: f64) -> Self {
+        let mut grid = SpatialGrid {
+            cell_size: cell_size.max(f64::MIN_POSITIVE),
+            cells: HashMap::new(),
+        };
+
+        for (index, &(x, y)) in positions.iter().enumerate() {
+            if x.is_finite() && y.is_finite() {
+                let key = grid.key(x, y);
+                grid.cells.entry(key).or_default().push(index);
+            }
+        }
+
+        grid
+    }
+
+    fn key(&self, x: f64, y: f64) -> (i64, i64) {
+        (
+            (x / self.cell_size).floor() as i64,
+            (y / self.cell_size).floor() as i64,
+        )
+    }
+
+    // Candidates within `cell_size` of (x, y): the 3x3 block of cells around it
+    pub fn neighbors(&self, x: f64, y: f64) -> impl Iterator<Item = usize> + '_ {
+        let (cx, cy) = self.key(x, y);
+        (-1..=1)
+            .flat_map(move |dx| (-1..=1).map(move |dy| (cx + dx, cy + dy)))
+            .filter_map(|key| self.cells.get(&key))
+            .flat_map(|indices| indices.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    // Overlapping pairs of discs (x, y, radius), found as CollisionForce does:
+    // a grid of the largest diameter, checking the 3x3 block around each disc
+    fn grid_pairs(discs: &[(f64, f64, f64)]) -> BTreeSet<(usize, usize)> {
+        let max_radius = discs.iter().map(|disc| disc.2).fold(0.0, f64::max);
+        let positions: Vec<(f64, f64)> = discs.iter().map(|&(x, y, _)| (x, y)).collect();
+        let grid = SpatialGrid::build(&positions, 2.0 * max_radius);
+
+        let mut pairs = BTreeSet::new();
+        for (i, &(x, y, radius)) in discs.iter().enumerate() {
+            for j in grid.neighbors(x, y).filter(|&j| j > i) {
+                if overlap((x, y, radius), discs[j]) {
+                    pairs.insert((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    fn overlap(a: (f64, f64, f64), b: (f64, f64, f64)) -> bool {
+        let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+        dx * dx + dy * dy < (a.2 + b.2) * (a.2 + b.2)
+    }
+
+    #[test]
+    fn grid_finds_the_same_overlaps_as_all_pairs() {
+        let mut rng = StdRng::seed_from_u64(3);
+        // Mostly small discs with a few large ones, some at negative coordinates
+        let discs: Vec<(f64, f64, f64)> = (0..600)
+            .map(|_| {
+                let radius = if rng.gen_bool(0.05) { rng.gen_range(20.0..40.0) } else { rng.gen_range(1.0..8.0) };
+                (rng.gen_range(-300.0..300.0), rng.gen_range(-300.0..300.0), radius)
+            })
+            .collect();
+
+        let mut all_pairs = BTreeSet::new();
+        for i in 0..discs.len() {
+            for j in i + 1..discs.len() {
+                if overlap(discs[i], discs[j]) {
+                    all_pairs.insert((i, j));
+                }
+            }
+        }
+
+        assert!(all_pairs.len() > 100, "only {} overlaps", all_pairs.len());
+        assert_eq!(grid_pairs(&discs), all_pairs);
+    }
+}